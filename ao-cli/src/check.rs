@@ -1,10 +1,13 @@
 use std::path::Path;
+use std::time::Instant;
 
 use anyhow::{bail, Context, Result};
 use tracing::info;
 
-use crate::config; // Import the config module
-use crate::utils::{find_project_root, run_tool}; // Import from utils
+use crate::config::{self, CheckStep}; // Import the config module
+use crate::coverage;
+use crate::event::{self, Event};
+use crate::utils::{changed_files_since, find_project_root, run_tool, run_tool_captured, OutputFormat, Report, StepReport}; // Import from utils
 
 /// Handler for `ao check`.
 /// Verifies structure, loads config, and runs configured linters/testers.
@@ -12,11 +15,24 @@ use crate::utils::{find_project_root, run_tool}; // Import from utils
 /// # Arguments
 ///
 /// * `path_str` - Path within the project directory to start searching from.
+/// * `format` - `Text` streams each tool's output as it runs (the default); `Json` captures
+///   output instead and emits a single `Report` to stdout once all steps have finished.
+/// * `changed_since` - When set, any linter/tester command containing a `{changed_files}`
+///   placeholder runs only against files that changed relative to this git ref (and is skipped
+///   entirely if none did). Commands without the placeholder are unaffected.
+///
+/// In `Text` mode, each linter/tester's wall-clock time is logged once it finishes, so a slow
+/// step in CI output is easy to spot. `Json` mode already carries this as `duration_ms` on each
+/// `StepReport`. When `AO_OUTPUT=json` is set, every step and a final summary are additionally
+/// emitted as NDJSON lines via `crate::event`, independent of `format`.
 ///
 /// # Errors
 ///
 /// Returns an error if any step (root finding, config load, structure check, tool execution) fails.
-pub fn run(path_str: String) -> Result<()> {
+/// In `Json` mode, all configured linters/testers still run even if an earlier one failed, and
+/// the report is printed before returning an error for the overall failure.
+pub fn run(path_str: String, format: OutputFormat, changed_since: Option<String>) -> Result<()> {
+    let run_started = Instant::now();
     let start_path = Path::new(&path_str);
     info!("Starting check from {}", start_path.display());
 
@@ -24,6 +40,7 @@ pub fn run(path_str: String) -> Result<()> {
     let project_path = find_project_root(start_path)
         .with_context(|| format!("Failed to find project root starting from '{}'", start_path.display()))?;
     info!("Found project root at {}", project_path.display());
+    event::project_root_resolved(&project_path);
 
     // Load configuration
     let config = config::load_config(&project_path)
@@ -90,12 +107,84 @@ pub fn run(path_str: String) -> Result<()> {
 
     // --- Tool Execution --- //
 
+    let default_timeout = config.check.timeout_duration();
+    let env = config.global_env();
+
+    let changed_files = match &changed_since {
+        Some(git_ref) => changed_files_since(&project_path, git_ref)?,
+        None => None,
+    };
+    let linters = resolve_changed_files_placeholder(&config.check.linters, &changed_files);
+    let testers = resolve_changed_files_placeholder(&config.check.testers, &changed_files);
+
+    if format == OutputFormat::Json {
+        let mut steps = Vec::new();
+        let mut success = true;
+
+        for linter in &linters {
+            event::emit(&Event::ToolStarted { command: linter.command().to_string() });
+            let output = run_tool_captured(linter.command(), &project_path, linter.timeout_duration(default_timeout), &env)
+                .with_context(|| format!("Linter command '{}' failed to execute", linter.command()))?;
+            success &= output.exit_code == 0;
+            event::emit(&Event::ToolFinished {
+                command: output.command.clone(),
+                exit_code: output.exit_code,
+                duration_ms: output.duration_ms,
+                success: output.exit_code == 0,
+            });
+            steps.push(StepReport::from(output));
+        }
+        for tester in &testers {
+            event::emit(&Event::ToolStarted { command: tester.command().to_string() });
+            let output = run_tool_captured(tester.command(), &project_path, tester.timeout_duration(default_timeout), &env)
+                .with_context(|| format!("Tester command '{}' failed to execute", tester.command()))?;
+            success &= output.exit_code == 0;
+            event::emit(&Event::ToolFinished {
+                command: output.command.clone(),
+                exit_code: output.exit_code,
+                duration_ms: output.duration_ms,
+                success: output.exit_code == 0,
+            });
+            steps.push(StepReport::from(output));
+        }
+
+        let combined_coverage = coverage::collect_coverage(&project_path, &config.check.coverage)
+            .context("Failed to collect coverage")?;
+        if let Some(combined) = &combined_coverage {
+            coverage::write_report(combined, &config.check.coverage, &project_path)
+                .context("Failed to write combined coverage report")?;
+            if coverage::check_fail_under(combined, &config.check.coverage).is_err() {
+                success = false;
+            }
+        }
+        let coverage_summary = combined_coverage.as_ref().map(coverage::CoverageSummary::from);
+
+        let report = Report { name: config.project.name.clone(), steps, success, coverage: coverage_summary };
+        println!("{}", serde_json::to_string_pretty(&report).context("Failed to serialize check report")?);
+        event::emit(&Event::Summary { success, duration_ms: run_started.elapsed().as_millis() });
+
+        if !success {
+            bail!("One or more check steps failed");
+        }
+        return Ok(());
+    }
+
     // Run configured linters
-    if !config.check.linters.is_empty() {
+    if !linters.is_empty() {
         info!("--- Running Linters ---");
-        for linter_cmd in &config.check.linters {
-            run_tool(linter_cmd, &project_path)
-                .with_context(|| format!("Linter command '{}' failed", linter_cmd))?;
+        for linter in &linters {
+            event::emit(&Event::ToolStarted { command: linter.command().to_string() });
+            let started = Instant::now();
+            run_tool(linter.command(), &project_path, linter.timeout_duration(default_timeout), &env)
+                .with_context(|| format!("Linter command '{}' failed", linter.command()))?;
+            let duration_ms = started.elapsed().as_millis();
+            info!("Linter '{}' finished in {}ms", linter.command(), duration_ms);
+            event::emit(&Event::ToolFinished {
+                command: linter.command().to_string(),
+                exit_code: 0,
+                duration_ms,
+                success: true,
+            });
         }
         info!("--- Linters Finished ---");
     } else {
@@ -103,21 +192,68 @@ pub fn run(path_str: String) -> Result<()> {
     }
 
     // Run configured testers
-    if !config.check.testers.is_empty() {
+    if !testers.is_empty() {
         info!("--- Running Testers ---");
-        for tester_cmd in &config.check.testers {
-            run_tool(tester_cmd, &project_path)
-                .with_context(|| format!("Tester command '{}' failed", tester_cmd))?;
+        for tester in &testers {
+            event::emit(&Event::ToolStarted { command: tester.command().to_string() });
+            let started = Instant::now();
+            run_tool(tester.command(), &project_path, tester.timeout_duration(default_timeout), &env)
+                .with_context(|| format!("Tester command '{}' failed", tester.command()))?;
+            let duration_ms = started.elapsed().as_millis();
+            info!("Tester '{}' finished in {}ms", tester.command(), duration_ms);
+            event::emit(&Event::ToolFinished {
+                command: tester.command().to_string(),
+                exit_code: 0,
+                duration_ms,
+                success: true,
+            });
         }
         info!("--- Testers Finished ---");
     } else {
         info!("No testers configured.");
     }
 
+    // --- Coverage Aggregation --- //
+    // Opt-in: only kicks in if a tester actually emitted a coverage file matching
+    // `[check.coverage].sources`.
+    if let Some(combined) = coverage::collect_coverage(&project_path, &config.check.coverage)
+        .context("Failed to collect coverage")?
+    {
+        coverage::write_report(&combined, &config.check.coverage, &project_path)
+            .context("Failed to write combined coverage report")?;
+        coverage::check_fail_under(&combined, &config.check.coverage)?;
+    }
+
     info!("All checks passed successfully!");
+    event::emit(&Event::Summary { success: true, duration_ms: run_started.elapsed().as_millis() });
     Ok(())
 }
 
+/// Substitutes `{changed_files}` in each command with the space-separated list of changed
+/// files. Commands without the placeholder are returned unchanged. When `changed_files` is
+/// `None` (no `--changed-since` given, or the project isn't a git repo) a command with the
+/// placeholder is also returned unchanged, since there is nothing to filter by. When a command
+/// has the placeholder but no files changed, it is skipped entirely.
+fn resolve_changed_files_placeholder(steps: &[CheckStep], changed_files: &Option<Vec<String>>) -> Vec<CheckStep> {
+    steps
+        .iter()
+        .filter_map(|step| {
+            let cmd = step.command();
+            if !cmd.contains("{changed_files}") {
+                return Some(step.clone());
+            }
+            match changed_files {
+                None => Some(step.clone()),
+                Some(files) if files.is_empty() => {
+                    info!("Skipping '{}': no changed files since the given ref", cmd);
+                    None
+                }
+                Some(files) => Some(step.with_command(cmd.replace("{changed_files}", &files.join(" ")))),
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,7 +269,7 @@ mod tests {
         let project_name = "check_test_project";
         let project_path = base_path.join(project_name);
         // Use init::run to create the structure
-        init::run(project_path.to_str().unwrap().to_string())
+        init::run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None)
             .context("init::run failed during test setup")?;
         Ok(project_path)
     }
@@ -187,7 +323,7 @@ mod tests {
     fn run_succeeds_when_called_from_root() {
         let tmp_dir = tempdir().unwrap();
         let project_path = setup_valid_project(tmp_dir.path()).unwrap();
-        let result = run(project_path.to_str().unwrap().to_string());
+        let result = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result.is_ok());
     }
 
@@ -196,7 +332,7 @@ mod tests {
         let tmp_dir = tempdir().unwrap();
         let project_path = setup_valid_project(tmp_dir.path()).unwrap();
         let models_path = project_path.join("models"); // 'models' dir is created by setup_valid_project via init::run
-        let result = run(models_path.to_str().unwrap().to_string());
+        let result = run(models_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result.is_ok());
     }
 
@@ -206,7 +342,7 @@ mod tests {
         let project_path = setup_valid_project(tmp_dir.path()).unwrap();
         add_check_config(&project_path); // Add [check] section
 
-        let result = run(project_path.to_str().unwrap().to_string());
+        let result = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result.is_ok());
         // We could capture stdout here to verify the print messages if needed
     }
@@ -215,7 +351,7 @@ mod tests {
     fn run_fails_if_path_does_not_exist() {
         let tmp_dir = tempdir().unwrap();
         let project_path = tmp_dir.path().join("non_existent_project");
-        let result = run(project_path.to_str().unwrap().to_string());
+        let result = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result.is_err());
         let err_str = result.unwrap_err().to_string();
         assert!(err_str.contains("Failed to find project root") || err_str.contains("Failed to canonicalize"));
@@ -226,7 +362,7 @@ mod tests {
         let tmp_dir = tempdir().unwrap();
         let empty_dir = tmp_dir.path().join("empty_dir");
         fs::create_dir(&empty_dir).unwrap();
-        let result = run(empty_dir.to_str().unwrap().to_string());
+        let result = run(empty_dir.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("project root"));
@@ -239,7 +375,7 @@ mod tests {
         // Overwrite with malformed config
         fs::write(project_path.join("ao.toml"), "[project]name=").unwrap();
 
-        let result = run(project_path.to_str().unwrap().to_string());
+        let result = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("parse") && err.contains("config"));
@@ -252,7 +388,7 @@ mod tests {
 
         // Test removing a required service directory
         fs::remove_dir_all(project_path.join("api-service")).unwrap();
-        let result_dir = run(project_path.to_str().unwrap().to_string());
+        let result_dir = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result_dir.is_err());
         let err_msg_dir = result_dir.unwrap_err().to_string();
         assert!(err_msg_dir.contains("Required directory") && err_msg_dir.contains("api-service"));
@@ -262,7 +398,7 @@ mod tests {
 
         // Test removing a required file within a service directory (proto)
         fs::remove_file(project_path.join("model-interface/anops.proto")).unwrap();
-        let result_file_proto = run(project_path.to_str().unwrap().to_string());
+        let result_file_proto = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result_file_proto.is_err());
         let err_msg_proto = result_file_proto.unwrap_err().to_string();
         assert!(err_msg_proto.contains("Required file") && err_msg_proto.contains("anops.proto") && err_msg_proto.contains("model-interface"));
@@ -272,9 +408,136 @@ mod tests {
 
         // Test removing a generated gRPC file
         fs::remove_file(project_path.join("api-service/anops_pb2.py")).unwrap();
-        let result_file_grpc = run(project_path.to_str().unwrap().to_string());
+        let result_file_grpc = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
         assert!(result_file_grpc.is_err());
         let err_msg_grpc = result_file_grpc.unwrap_err().to_string();
         assert!(err_msg_grpc.contains("Required file") && err_msg_grpc.contains("anops_pb2.py") && err_msg_grpc.contains("api-service"));
     }
+
+    #[test]
+    fn run_fails_if_tester_exceeds_check_timeout() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = setup_valid_project(tmp_dir.path()).unwrap();
+        let config_path = project_path.join("ao.toml");
+        let mut content = fs::read_to_string(&config_path).unwrap();
+        content.push_str("\n[check]\ntimeout = 1\ntesters = [\"sleep 5\"]\n");
+        fs::write(config_path, content).unwrap();
+
+        let result = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
+        assert!(result.is_err());
+        assert!(format!("{:#}", result.unwrap_err()).contains("timed out after"));
+    }
+
+    #[test]
+    fn run_injects_global_env_vars_into_linter_commands() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = setup_valid_project(tmp_dir.path()).unwrap();
+        let config_path = project_path.join("ao.toml");
+        let mut content = fs::read_to_string(&config_path).unwrap();
+        content.push_str(
+            r#"
+[env]
+AO_CHECK_GREETING = "hi"
+
+[check]
+linters = ["sh -c 'test \"$AO_CHECK_GREETING\" = hi'"]
+"#,
+        );
+        fs::write(config_path, content).unwrap();
+
+        let result = run(project_path.to_str().unwrap().to_string(), OutputFormat::Text, None);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn run_json_format_succeeds_and_reports_all_steps() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = setup_valid_project(tmp_dir.path()).unwrap();
+        add_check_config(&project_path);
+
+        let result = run(project_path.to_str().unwrap().to_string(), OutputFormat::Json, None);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn run_json_format_fails_but_still_reports_when_a_step_fails() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = setup_valid_project(tmp_dir.path()).unwrap();
+        let config_path = project_path.join("ao.toml");
+        let mut content = fs::read_to_string(&config_path).unwrap();
+        content.push_str("\n[check]\nlinters = [\"ls non_existent_file_for_json_check\"]\n");
+        fs::write(config_path, content).unwrap();
+
+        let result = run(project_path.to_str().unwrap().to_string(), OutputFormat::Json, None);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("One or more check steps failed"));
+    }
+
+    // Helper to turn a project directory into a git repo with one commit.
+    fn init_git_repo(project_path: &Path) {
+        let run_git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(project_path)
+                .status()
+                .unwrap();
+        };
+        run_git(&["init", "-q"]);
+        run_git(&["-c", "user.email=test@example.com", "-c", "user.name=Test", "add", "."]);
+        run_git(&["-c", "user.email=test@example.com", "-c", "user.name=Test", "commit", "-q", "-m", "initial"]);
+    }
+
+    #[test]
+    fn run_changed_since_skips_linter_when_no_files_changed() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = setup_valid_project(tmp_dir.path()).unwrap();
+        let config_path = project_path.join("ao.toml");
+        let mut content = fs::read_to_string(&config_path).unwrap();
+        content.push_str("\n[check]\nlinters = [\"ls {changed_files}\"]\n");
+        fs::write(config_path, content).unwrap();
+        init_git_repo(&project_path);
+
+        let result = run(
+            project_path.to_str().unwrap().to_string(),
+            OutputFormat::Text,
+            Some("HEAD".to_string()),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn run_changed_since_substitutes_changed_files_into_linter_command() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = setup_valid_project(tmp_dir.path()).unwrap();
+        init_git_repo(&project_path);
+        fs::write(project_path.join("model-interface/anops.proto"), "// changed\n").unwrap();
+        let config_path = project_path.join("ao.toml");
+        let mut content = fs::read_to_string(&config_path).unwrap();
+        content.push_str("\n[check]\nlinters = [\"ls {changed_files}\"]\n");
+        fs::write(config_path, content).unwrap();
+
+        let result = run(
+            project_path.to_str().unwrap().to_string(),
+            OutputFormat::Text,
+            Some("HEAD".to_string()),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn run_changed_since_falls_back_to_linting_everything_outside_a_git_repo() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = setup_valid_project(tmp_dir.path()).unwrap();
+        let config_path = project_path.join("ao.toml");
+        let mut content = fs::read_to_string(&config_path).unwrap();
+        content.push_str("\n[check]\nlinters = [\"echo {changed_files}\"]\n");
+        fs::write(config_path, content).unwrap();
+
+        let result = run(
+            project_path.to_str().unwrap().to_string(),
+            OutputFormat::Text,
+            Some("HEAD".to_string()),
+        );
+        assert!(result.is_ok());
+    }
 }
\ No newline at end of file
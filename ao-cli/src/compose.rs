@@ -0,0 +1,190 @@
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// Top-level shape written to `docker-compose.yml`. Targets the modern Compose Specification:
+/// just `services`/`networks`, with no mandatory `version` key.
+#[derive(Debug, Serialize)]
+pub struct ComposeFile {
+    pub services: BTreeMap<String, ComposeService>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub networks: BTreeMap<String, ComposeNetwork>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComposeService {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub environment: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub networks: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComposeNetwork {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+}
+
+/// Validates `config.services`, then renders them as a `docker-compose.yml` document.
+///
+/// # Errors
+///
+/// Returns an error if a service's `depends_on` names a service that isn't defined in
+/// `[services]`, if two services publish the same host port, or if serialization fails.
+pub fn generate_compose_yaml(config: &Config) -> Result<String> {
+    validate_services(config)?;
+
+    let mut services = BTreeMap::new();
+    let mut networks: BTreeMap<String, ComposeNetwork> = BTreeMap::new();
+    for (name, service) in &config.services {
+        for network_name in &service.networks {
+            networks
+                .entry(network_name.clone())
+                .or_insert(ComposeNetwork { driver: Some("bridge".to_string()) });
+        }
+
+        services.insert(
+            name.clone(),
+            ComposeService {
+                build: service.build.clone(),
+                image: service.image.clone(),
+                ports: service.ports.clone(),
+                environment: service.environment.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+                depends_on: service.depends_on.clone(),
+                networks: service.networks.clone(),
+            },
+        );
+    }
+
+    let compose = ComposeFile { services, networks };
+    serde_yaml::to_string(&compose).context("Failed to serialize docker-compose.yml")
+}
+
+/// Checks that every `depends_on` entry names a service defined in `[services]`, and that no
+/// two services publish the same host port.
+fn validate_services(config: &Config) -> Result<()> {
+    for (name, service) in &config.services {
+        for dependency in &service.depends_on {
+            if !config.services.contains_key(dependency) {
+                bail!("Service '{}' has depends_on referencing undefined service '{}'", name, dependency);
+            }
+        }
+    }
+
+    let mut seen_host_ports: HashSet<&str> = HashSet::new();
+    for (name, service) in &config.services {
+        for mapping in &service.ports {
+            let host_port = mapping.split(':').next().unwrap_or(mapping);
+            if !seen_host_ports.insert(host_port) {
+                bail!("Port '{}' in service '{}' collides with another service's published port", host_port, name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_config;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn generate_compose_yaml_renders_services_and_networks() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "compose-project"
+
+[services.api]
+build = "./api-service"
+ports = ["8000:8000"]
+depends_on = ["model"]
+networks = ["anops-net"]
+
+[services.model]
+build = "./model-service"
+networks = ["anops-net"]
+"#;
+        fs::write(tmp_dir.path().join("ao.toml"), config_content).unwrap();
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        let yaml = generate_compose_yaml(&config).unwrap();
+        assert!(yaml.contains("services:"));
+        assert!(yaml.contains("api:"));
+        assert!(yaml.contains("model:"));
+        assert!(yaml.contains("anops-net"));
+        assert!(!yaml.contains("version:"));
+    }
+
+    #[test]
+    fn generate_compose_yaml_fails_on_undefined_dependency() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "compose-project"
+
+[services.api]
+build = "./api-service"
+depends_on = ["does_not_exist"]
+"#;
+        fs::write(tmp_dir.path().join("ao.toml"), config_content).unwrap();
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        let result = generate_compose_yaml(&config);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("does_not_exist"));
+    }
+
+    #[test]
+    fn generate_compose_yaml_fails_on_port_collision() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "compose-project"
+
+[services.api]
+build = "./api-service"
+ports = ["8000:80"]
+
+[services.admin]
+build = "./admin-service"
+ports = ["8000:81"]
+"#;
+        fs::write(tmp_dir.path().join("ao.toml"), config_content).unwrap();
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        let result = generate_compose_yaml(&config);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("collides"));
+    }
+
+    #[test]
+    fn load_config_rejects_unknown_service_key() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "compose-project"
+
+[services.api]
+build = "./api-service"
+typo_field = "oops"
+"#;
+        fs::write(tmp_dir.path().join("ao.toml"), config_content).unwrap();
+
+        let result = load_config(tmp_dir.path());
+        assert!(result.is_err());
+        assert!(format!("{:#}", result.unwrap_err()).contains("typo_field"));
+    }
+}
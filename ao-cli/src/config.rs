@@ -1,8 +1,10 @@
+use clap::ValueEnum;
 use serde::Deserialize;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use std::fs;
-use std::path::Path;
-use std::collections::HashMap; // Added HashMap
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
 /// Represents the overall configuration loaded from ao.toml
 #[derive(Deserialize, Debug, PartialEq, Default)]
@@ -11,26 +13,524 @@ pub struct Config {
     #[serde(default)]
     pub check: CheckConfig,
     #[serde(default)] // Use default HashMap if missing
-    pub tasks: HashMap<String, Vec<String>>,
+    pub tasks: HashMap<String, TaskDef>,
+    /// Global environment variables injected into every command `ao run`/`ao check` executes.
+    /// A task's own `env` table overrides these on a per-key basis; see `task_env`.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Declarative service topology, rendered into `docker-compose.yml` by `crate::compose`.
+    #[serde(default)]
+    pub services: HashMap<String, ServiceConfig>,
+    /// Short names that resolve to a task in `[tasks]`, e.g. `t = "test"`. Looked up by
+    /// `Config::resolve_alias` before `ao run` looks a name up in `tasks`; `ao list` shows each
+    /// alias beside the task it resolves to.
+    #[serde(default)]
+    pub alias: HashMap<String, String>,
+    /// Proto-to-stub generators run by `ao build`. Empty (the default) falls back to the
+    /// legacy single python generator targeting `api-service`/`model-service` from
+    /// `model-interface`; see `crate::utils::generate_grpc_code`.
+    #[serde(default)]
+    pub codegen: CodegenConfig,
+    /// Settings for `ao build`'s image-building step, see `[build]`.
+    #[serde(default)]
+    pub build: BuildConfig,
+}
+
+/// Represents the `[build]` table in ao.toml.
+#[derive(Deserialize, Debug, PartialEq, Default)]
+pub struct BuildConfig {
+    /// Which `crate::container::ContainerBackend` builds service images: `"docker"`, `"podman"`,
+    /// `"buildah"`, or `"nerdctl"`. `None` (the default) auto-detects the first of those whose
+    /// binary is runnable.
+    #[serde(default)]
+    pub backend: Option<String>,
+
+    /// When `true`, images are tagged `{short_commit}` (with a `-dirty` suffix on an unclean
+    /// working tree) in addition to `:latest`, via `crate::utils::git_image_tag`. Falls back to
+    /// `:latest` only when the project isn't inside a git repository or the repository has no
+    /// commits yet. Defaults to `false`.
+    #[serde(default)]
+    pub tag_with_git: bool,
+}
+
+/// A single service entry in the `[services]` table, describing one `docker-compose.yml`
+/// service. Unknown keys are rejected at parse time to catch typos early.
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceConfig {
+    /// Build context directory (mutually exclusive with `image` in practice, but both are
+    /// accepted as-is and left for `crate::compose` to render).
+    #[serde(default)]
+    pub build: Option<String>,
+    /// A pre-built image reference to run instead of building one.
+    #[serde(default)]
+    pub image: Option<String>,
+    /// `"host:container"` port mappings.
+    #[serde(default)]
+    pub ports: Vec<String>,
+    /// Environment variables passed to the container.
+    #[serde(default)]
+    pub environment: HashMap<String, String>,
+    /// Names of other `[services]` entries that must start before this one.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Names of compose networks this service joins.
+    #[serde(default)]
+    pub networks: Vec<String>,
+}
+
+/// Represents the `[codegen]` table in ao.toml: zero or more proto-to-stub generators run by
+/// `ao build` (see `crate::utils::generate_grpc_code`). An empty `generators` list (the
+/// default, and what a bare `ao.toml` with no `[codegen]` table at all deserializes to) means
+/// "use the legacy single python generator", so existing projects don't need to add this table.
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CodegenConfig {
+    #[serde(default)]
+    pub generators: Vec<CodegenGenerator>,
+}
+
+/// One `.proto` source and the stub target(s) generated from it. All of a generator's targets
+/// are requested in a single `protoc` invocation, the same way the legacy python generator
+/// always has.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CodegenGenerator {
+    /// Directory (relative to the project root) containing the `.proto` file.
+    pub proto_dir: String,
+    /// `.proto` file name within `proto_dir`.
+    #[serde(default = "default_proto_file")]
+    pub proto_file: String,
+    /// Stub outputs to generate from this source.
+    pub targets: Vec<CodegenTarget>,
+}
+
+fn default_proto_file() -> String {
+    "anops.proto".to_string()
+}
+
+/// A single generated-stub output: which plugin to invoke and where to write its output.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CodegenTarget {
+    pub language: CodegenLanguage,
+    /// Directory (relative to the project root) this target's stubs are written into.
+    pub out_dir: String,
+    /// Extra arguments appended verbatim to the `protoc` invocation for this target.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+/// A stub-generation plugin selectable in `[[codegen.generators.targets]]`.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CodegenLanguage {
+    /// `grpc_tools.protoc`'s python/pyi/grpc_python outputs.
+    Python,
+    /// Rust stubs via the `tonic`/`prost` protoc plugins.
+    Tonic,
+    Go,
+    Ts,
+}
+
+impl Config {
+    /// Merges the global `[env]` table with `task`'s own `env` overrides (task wins on
+    /// conflicting keys). Used to build the env map passed to `ToolRunner`/`run_tool`.
+    pub fn task_env(&self, task: &TaskDef) -> BTreeMap<String, String> {
+        let mut merged: BTreeMap<String, String> =
+            self.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        merged.extend(task.env().iter().map(|(k, v)| (k.clone(), v.clone())));
+        merged
+    }
+
+    /// The global `[env]` table, for commands (like `check`'s linters/testers) that aren't
+    /// associated with a specific task.
+    pub fn global_env(&self) -> BTreeMap<String, String> {
+        self.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    }
+
+    /// Resolves `name` through `[alias]` if it names one, otherwise returns it unchanged. Does
+    /// not check whether the resolved name is an actual task in `[tasks]`.
+    pub fn resolve_alias<'a>(&'a self, name: &'a str) -> &'a str {
+        self.alias.get(name).map(String::as_str).unwrap_or(name)
+    }
+
+    /// Resolves the execution order for `task_name` and everything it transitively depends on,
+    /// via Kahn's algorithm, so every task runs only after its own dependencies have. The
+    /// requested task is always last in the returned order.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `task_name`, or any task it (transitively) depends on, isn't defined in
+    /// `[tasks]`, or if the dependency graph contains a cycle (the error reports the tasks
+    /// left over once no more in-degree-0 nodes remain).
+    pub fn resolve_task_order(&self, task_name: &str) -> Result<Vec<String>> {
+        // Discover every task reachable from `task_name` via `dependencies`, erroring as soon
+        // as a dependency names a task that doesn't exist.
+        let mut reachable: HashSet<String> = HashSet::new();
+        let mut stack: Vec<(Option<String>, String)> = vec![(None, task_name.to_string())];
+        while let Some((parent, name)) = stack.pop() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+            let task = self.tasks.get(&name).ok_or_else(|| match &parent {
+                Some(parent_name) => anyhow::anyhow!("Task '{}' depends on undefined task '{}'", parent_name, name),
+                None => anyhow::anyhow!("Task '{}' not found in ao.toml", name),
+            })?;
+            for dep in task.dependencies() {
+                stack.push((Some(name.clone()), dep.clone()));
+            }
+        }
+
+        // Kahn's algorithm over the reachable subgraph: in-degree(n) is the number of
+        // reachable tasks that list n as a dependency.
+        let mut in_degree: HashMap<String, usize> = reachable.iter().map(|n| (n.clone(), 0)).collect();
+        let mut successors: HashMap<String, Vec<String>> = reachable.iter().map(|n| (n.clone(), Vec::new())).collect();
+        for name in &reachable {
+            for dep in self.tasks[name].dependencies() {
+                *in_degree.get_mut(name).unwrap() += 1;
+                successors.get_mut(dep).unwrap().push(name.clone());
+            }
+        }
+
+        let mut queue: VecDeque<String> = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+        queue.make_contiguous().sort();
+
+        let mut order = Vec::new();
+        while let Some(name) = queue.pop_front() {
+            order.push(name.clone());
+            for successor in &successors[&name] {
+                let degree = in_degree.get_mut(successor).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(successor.clone());
+                }
+            }
+        }
+
+        if order.len() < reachable.len() {
+            let ordered: HashSet<String> = order.iter().cloned().collect();
+            let mut remaining: Vec<String> = reachable.difference(&ordered).cloned().collect();
+            remaining.sort();
+            bail!("Cycle detected in task dependencies: {}", remaining.join(", "));
+        }
+
+        Ok(order)
+    }
 }
 
 /// Represents the [project] table in ao.toml
 #[derive(Deserialize, Debug, PartialEq, Default)] // Added Default
 pub struct ProjectConfig {
     pub name: String,
+    /// Language backend used to scaffold `api-service` and `model-service` in `ao init`.
+    /// Overridden per-service by `api_service_language`/`model_service_language`.
+    #[serde(default)]
+    pub language: Language,
+    /// Overrides `language` for `api-service` only.
+    #[serde(default)]
+    pub api_service_language: Option<Language>,
+    /// Overrides `language` for `model-service` only.
+    #[serde(default)]
+    pub model_service_language: Option<Language>,
+}
+
+/// A language backend `ao init` can scaffold a service in. See `crate::init::ServiceBackend`.
+#[derive(ValueEnum, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+#[value(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    Python,
+    R,
+}
+
+/// Whether `ao init` initializes a VCS repo in the new project directory, selected via
+/// `ao init --vcs <git|none>`. Mirrors the `--vcs` flag cargo-temp offers when scaffolding a
+/// throwaway project.
+#[derive(ValueEnum, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[value(rename_all = "lowercase")]
+pub enum VcsMode {
+    /// Run `git init` in the new project directory, skipping gracefully (with a warning,
+    /// not an error) if the `git` binary isn't on PATH.
+    #[default]
+    Git,
+    /// Don't initialize any VCS.
+    None,
 }
 
 /// Represents the [check] table in ao.toml
 #[derive(Deserialize, Debug, PartialEq, Default)]
 pub struct CheckConfig {
     #[serde(default)]
-    pub linters: Vec<String>,
+    pub linters: Vec<CheckStep>,
+    #[serde(default)]
+    pub testers: Vec<CheckStep>,
+    /// Upper bound, in seconds, for each linter/tester step that doesn't set its own `timeout`.
+    /// `None` (or `0`) means no limit.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Coverage aggregation settings, see `[check.coverage]`.
+    #[serde(default)]
+    pub coverage: CoverageConfig,
+}
+
+impl CheckConfig {
+    /// Returns the configured default timeout as a `Duration`, treating a missing or zero value
+    /// as "no limit". Individual steps may override this; see `CheckStep::timeout_duration`.
+    pub fn timeout_duration(&self) -> Option<Duration> {
+        self.timeout.filter(|secs| *secs > 0).map(Duration::from_secs)
+    }
+}
+
+/// A single entry in `[check].linters` or `[check].testers`.
+///
+/// Accepts either a bare command string (`linters = ["ruff check ."]`) or a table giving the
+/// command its own `timeout` in seconds, overriding `[check].timeout` for just that step.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum CheckStep {
+    Command(String),
+    Detailed {
+        command: String,
+        #[serde(default)]
+        timeout: Option<u64>,
+    },
+}
+
+impl CheckStep {
+    pub fn command(&self) -> &str {
+        match self {
+            CheckStep::Command(command) => command,
+            CheckStep::Detailed { command, .. } => command,
+        }
+    }
+
+    /// This step's own timeout if it set one, falling back to `default` (typically
+    /// `CheckConfig::timeout_duration()`) otherwise.
+    pub fn timeout_duration(&self, default: Option<Duration>) -> Option<Duration> {
+        match self {
+            CheckStep::Command(_) => default,
+            CheckStep::Detailed { timeout, .. } => {
+                timeout.filter(|secs| *secs > 0).map(Duration::from_secs).or(default)
+            }
+        }
+    }
+
+    /// Returns a copy of this step with its command replaced, preserving a `Detailed` step's own
+    /// `timeout` (used by `check::resolve_changed_files_placeholder`).
+    pub fn with_command(&self, command: String) -> CheckStep {
+        match self {
+            CheckStep::Command(_) => CheckStep::Command(command),
+            CheckStep::Detailed { timeout, .. } => CheckStep::Detailed { command, timeout: *timeout },
+        }
+    }
+}
+
+/// Combined-report format for `[check.coverage]`, written by `crate::coverage::write_report`.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageFormat {
+    Xml,
+    Html,
+    #[default]
+    Term,
+}
+
+/// Represents the `[check.coverage]` table in ao.toml. When `[check].testers` commands emit
+/// per-service coverage XML (e.g. `coverage.xml` from `pytest --cov`), these settings control how
+/// the results from every service are merged into a single combined report.
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CoverageConfig {
+    /// Output format for the combined report. Defaults to `term`, which only logs a summary.
+    #[serde(default)]
+    pub format: CoverageFormat,
+    /// Where to write the combined report, relative to the project root. Required when `format`
+    /// is `xml` or `html`; unused for `term`.
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Minimum combined line-coverage percentage (0-100) required for `ao check` to pass. `None`
+    /// means no threshold is enforced.
     #[serde(default)]
-    pub testers: Vec<String>,
+    pub fail_under: Option<f64>,
+    /// Glob patterns (relative to the project root) for per-service Cobertura-style coverage XML
+    /// files to merge. Defaults to one level of service directories, e.g. `api-service/coverage.xml`.
+    #[serde(default = "default_coverage_sources")]
+    pub sources: Vec<String>,
+}
+
+fn default_coverage_sources() -> Vec<String> {
+    vec!["*/coverage.xml".to_string()]
+}
+
+/// Name of the default, built-in task execution backend (a plain shell command).
+pub const DEFAULT_RUNNER: &str = "shell";
+
+/// Gates whether a task runs at all. Evaluated against the real process environment and
+/// `std::env::consts::OS`, independent of the `env`/`${VAR}` substitution used for commands.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum TaskCondition {
+    /// Only run on this OS, e.g. `"linux"`, `"macos"`, `"windows"` (matched against
+    /// `std::env::consts::OS`).
+    Os { os: String },
+    /// Only run if this environment variable is set, regardless of its value.
+    EnvSet { env_set: String },
+    /// Only run if this environment variable is set to exactly this value.
+    EnvEq { env_eq: String, value: String },
+}
+
+impl TaskCondition {
+    /// Evaluates the condition against the current process environment.
+    pub fn is_met(&self) -> bool {
+        match self {
+            TaskCondition::Os { os } => std::env::consts::OS == os,
+            TaskCondition::EnvSet { env_set } => std::env::var(env_set).is_ok(),
+            TaskCondition::EnvEq { env_eq, value } => {
+                std::env::var(env_eq).map(|actual| &actual == value).unwrap_or(false)
+            }
+        }
+    }
+}
+
+/// A single task entry in the `[tasks]` table.
+///
+/// Accepts either the legacy bare array of commands (`build = ["echo hi"]`) or a
+/// table with a `commands` list plus a per-task `timeout` in seconds, for incremental
+/// execution `inputs`/`outputs` glob lists, a `runner` selecting the execution backend
+/// (`"shell"`, the default, or `"docker"`), a per-task `env` table, `dependencies` (or its
+/// aliases `depends_on`/`needs`) on other tasks, and a `condition` gating whether it runs at all.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum TaskDef {
+    Commands(Vec<String>),
+    Detailed {
+        commands: Vec<String>,
+        #[serde(default)]
+        timeout: Option<u64>,
+        /// Glob patterns (relative to the project root) whose combined fingerprint determines
+        /// whether the task needs to run. An empty list (the default) means "always run".
+        #[serde(default)]
+        inputs: Vec<String>,
+        /// Glob patterns that must all exist for a cached fingerprint match to count as
+        /// up to date; if any are missing the task reruns regardless of the fingerprint.
+        #[serde(default)]
+        outputs: Vec<String>,
+        /// Which `ToolRunner` backend executes this task's commands. `None` means `"shell"`.
+        #[serde(default)]
+        runner: Option<String>,
+        /// Per-task environment variables, overriding the global `[env]` table on conflicts.
+        #[serde(default)]
+        env: HashMap<String, String>,
+        /// Names of other tasks that must run (successfully) before this one does, per
+        /// `Config::resolve_task_order` and `crate::scheduler`. Also accepts `depends_on` or
+        /// `needs` as spellings.
+        #[serde(default, alias = "depends_on", alias = "needs")]
+        dependencies: Vec<String>,
+        /// Gates execution of this task; skipped entirely (including its dependents'
+        /// execution of it) when the condition isn't met.
+        #[serde(default)]
+        condition: Option<TaskCondition>,
+        /// One-line summary shown by `ao list`. Purely descriptive; doesn't affect execution.
+        #[serde(default)]
+        description: Option<String>,
+    },
+}
+
+impl TaskDef {
+    pub fn commands(&self) -> &[String] {
+        match self {
+            TaskDef::Commands(cmds) => cmds,
+            TaskDef::Detailed { commands, .. } => commands,
+        }
+    }
+
+    /// Returns the configured timeout as a `Duration`, treating a missing or zero value as "no limit".
+    pub fn timeout_duration(&self) -> Option<Duration> {
+        match self {
+            TaskDef::Commands(_) => None,
+            TaskDef::Detailed { timeout, .. } => timeout.filter(|secs| *secs > 0).map(Duration::from_secs),
+        }
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        match self {
+            TaskDef::Commands(_) => &[],
+            TaskDef::Detailed { inputs, .. } => inputs,
+        }
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        match self {
+            TaskDef::Commands(_) => &[],
+            TaskDef::Detailed { outputs, .. } => outputs,
+        }
+    }
+
+    /// Name of the `ToolRunner` backend this task executes under. Defaults to `"shell"`.
+    pub fn runner(&self) -> &str {
+        match self {
+            TaskDef::Commands(_) => DEFAULT_RUNNER,
+            TaskDef::Detailed { runner, .. } => runner.as_deref().unwrap_or(DEFAULT_RUNNER),
+        }
+    }
+
+    /// This task's own environment variable overrides (see `Config::task_env`).
+    pub fn env(&self) -> HashMap<String, String> {
+        match self {
+            TaskDef::Commands(_) => HashMap::new(),
+            TaskDef::Detailed { env, .. } => env.clone(),
+        }
+    }
+
+    /// Names of other tasks that must run before this one, per `Config::resolve_task_order`
+    /// (accepts `dependencies`, `depends_on`, or `needs` in `ao.toml`).
+    pub fn dependencies(&self) -> &[String] {
+        match self {
+            TaskDef::Commands(_) => &[],
+            TaskDef::Detailed { dependencies, .. } => dependencies,
+        }
+    }
+
+    /// The condition (if any) gating whether this task runs.
+    pub fn condition(&self) -> Option<&TaskCondition> {
+        match self {
+            TaskDef::Commands(_) => None,
+            TaskDef::Detailed { condition, .. } => condition.as_ref(),
+        }
+    }
+
+    /// One-line description shown by `ao list`, if the task declared one.
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            TaskDef::Commands(_) => None,
+            TaskDef::Detailed { description, .. } => description.as_deref(),
+        }
+    }
 }
 
 
-/// Loads the configuration from the ao.toml file in the project root.
+/// Loads the configuration for a project, layering (lowest to highest precedence):
+///
+/// 1. An optional user-global config at `$XDG_CONFIG_HOME/anops/config.toml` (falling back to
+///    `$HOME/.config/anops/config.toml`), letting a developer set machine-wide defaults.
+/// 2. The project's own `ao.toml`.
+/// 3. Environment overrides: an `AO__SECTION__KEY=value` variable overrides `[section] key`
+///    (double underscore separates nesting, e.g. `AO__CHECK__TIMEOUT=30`).
+///
+/// Tables merge key-by-key (a higher layer only needs to set the keys it wants to change);
+/// arrays are appended, later layers' items following earlier ones; any other value is replaced
+/// outright by the higher layer.
+///
+/// Once merged, every string value is run through `${VAR}` / `${VAR:-default}` interpolation
+/// against the real process environment, so e.g. `registry = "${REGISTRY:-docker.io}"` resolves
+/// per-machine without editing the TOML. This happens on the raw TOML value tree, before the
+/// typed `Config` is deserialized, so new fields are covered automatically.
 ///
 /// # Arguments
 ///
@@ -38,25 +538,190 @@ pub struct CheckConfig {
 ///
 /// # Errors
 ///
-/// Returns an error if the config file cannot be read or parsed.
+/// Returns an error if the project's `ao.toml` is missing, any layer fails to parse, a `${VAR}`
+/// placeholder has no default and its variable isn't set, or the merged result doesn't match
+/// `Config`'s shape.
 pub fn load_config(project_root: &Path) -> Result<Config> {
     let config_path = project_root.join("ao.toml");
-    println!("Loading config from: {:?}", config_path);
 
     if !config_path.exists() {
         anyhow::bail!("Configuration file not found: {}", config_path.display());
     }
 
-    let config_content = fs::read_to_string(&config_path)
+    let project_content = fs::read_to_string(&config_path)
         .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+    let project_value: toml::Value = toml::from_str(&project_content)
+        .with_context(|| format!("Failed to parse TOML config file: {}", config_path.display()))?;
 
-    let config: Config = toml::from_str(&config_content)
+    let mut merged = match load_global_config_value()? {
+        Some(global_value) => merge_toml_values(global_value, project_value),
+        None => project_value,
+    };
+    merged = merge_toml_values(merged, env_override_value());
+    merged = interpolate_value(merged, "").context("Failed to interpolate ${VAR} placeholders in config")?;
+
+    let config: Config = Config::deserialize(merged)
         .with_context(|| format!("Failed to parse TOML config file: {}", config_path.display()))?;
 
-    println!("Config loaded successfully: {:?}", config);
     Ok(config)
 }
 
+/// Loads the optional user-global config layer, or `None` if it doesn't exist.
+fn load_global_config_value() -> Result<Option<toml::Value>> {
+    let Some(path) = global_config_path() else {
+        return Ok(None);
+    };
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read global config file: {}", path.display()))?;
+    let value: toml::Value = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse global config file: {}", path.display()))?;
+    Ok(Some(value))
+}
+
+/// `$XDG_CONFIG_HOME/anops/config.toml`, falling back to `$HOME/.config/anops/config.toml`.
+/// `None` if neither `XDG_CONFIG_HOME` nor `HOME` is set.
+fn global_config_path() -> Option<PathBuf> {
+    if let Ok(xdg_config_home) = std::env::var("XDG_CONFIG_HOME") {
+        if !xdg_config_home.is_empty() {
+            return Some(Path::new(&xdg_config_home).join("anops").join("config.toml"));
+        }
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| Path::new(&home).join(".config").join("anops").join("config.toml"))
+}
+
+/// Builds a TOML overlay table from `AO__SECTION__KEY=value` environment variables (double
+/// underscore separates nesting levels; keys are lowercased, e.g. `AO__CHECK__TIMEOUT=30`
+/// overrides `[check] timeout`). Values are parsed as TOML booleans/integers/floats where
+/// possible, falling back to plain strings.
+fn env_override_value() -> toml::Value {
+    let mut root = toml::value::Table::new();
+    for (key, value) in std::env::vars() {
+        let Some(path) = key.strip_prefix("AO__") else { continue };
+        let segments: Vec<String> = path.split("__").map(|s| s.to_lowercase()).collect();
+        if segments.is_empty() || segments.iter().any(|s| s.is_empty()) {
+            continue;
+        }
+        insert_nested(&mut root, &segments, parse_env_scalar(&value));
+    }
+    toml::Value::Table(root)
+}
+
+fn parse_env_scalar(raw: &str) -> toml::Value {
+    if let Ok(b) = raw.parse::<bool>() {
+        toml::Value::Boolean(b)
+    } else if let Ok(i) = raw.parse::<i64>() {
+        toml::Value::Integer(i)
+    } else if let Ok(f) = raw.parse::<f64>() {
+        toml::Value::Float(f)
+    } else {
+        toml::Value::String(raw.to_string())
+    }
+}
+
+fn insert_nested(table: &mut toml::value::Table, segments: &[String], value: toml::Value) {
+    match segments {
+        [last] => {
+            table.insert(last.clone(), value);
+        }
+        [first, rest @ ..] => {
+            let entry = table
+                .entry(first.clone())
+                .or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+            if let toml::Value::Table(nested) = entry {
+                insert_nested(nested, rest, value);
+            }
+        }
+        [] => {}
+    }
+}
+
+/// Merges `overlay` onto `base`: tables merge key-by-key (overlay wins on conflicts), arrays
+/// are appended (base items first, then overlay's), and anything else is replaced by `overlay`.
+fn merge_toml_values(base: toml::Value, overlay: toml::Value) -> toml::Value {
+    match (base, overlay) {
+        (toml::Value::Table(mut base_table), toml::Value::Table(overlay_table)) => {
+            for (key, overlay_value) in overlay_table {
+                let merged_value = match base_table.remove(&key) {
+                    Some(base_value) => merge_toml_values(base_value, overlay_value),
+                    None => overlay_value,
+                };
+                base_table.insert(key, merged_value);
+            }
+            toml::Value::Table(base_table)
+        }
+        (toml::Value::Array(mut base_items), toml::Value::Array(overlay_items)) => {
+            base_items.extend(overlay_items);
+            toml::Value::Array(base_items)
+        }
+        (_, overlay) => overlay,
+    }
+}
+
+/// Recursively substitutes `${VAR}` / `${VAR:-default}` in every string in `value`. `path` is
+/// the dotted key path to `value` (e.g. `"check.linters[0]"`), used to name the offending key
+/// in interpolation errors.
+fn interpolate_value(value: toml::Value, path: &str) -> Result<toml::Value> {
+    match value {
+        toml::Value::String(s) => Ok(toml::Value::String(interpolate_string(&s, path)?)),
+        toml::Value::Array(items) => {
+            let interpolated = items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| interpolate_value(item, &format!("{}[{}]", path, i)))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(toml::Value::Array(interpolated))
+        }
+        toml::Value::Table(table) => {
+            let mut interpolated = toml::value::Table::new();
+            for (key, child) in table {
+                let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                interpolated.insert(key, interpolate_value(child, &child_path)?);
+            }
+            Ok(toml::Value::Table(interpolated))
+        }
+        other => Ok(other),
+    }
+}
+
+/// Substitutes `${VAR}` / `${VAR:-default}` placeholders in `value` against the real process
+/// environment. Fails, naming `key_path`, when a referenced variable is unset and has no default.
+fn interpolate_string(value: &str, key_path: &str) -> Result<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| anyhow!("Unterminated '${{' in key '{}' (value: '{}')", key_path, value))?;
+        let inner = &after[..end];
+        let (var_name, default) = match inner.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (inner, None),
+        };
+        let resolved = match std::env::var(var_name) {
+            Ok(resolved) => resolved,
+            Err(_) => default.map(str::to_string).ok_or_else(|| {
+                anyhow!(
+                    "Undefined environment variable '{}' referenced in key '{}' (value: '{}')",
+                    var_name,
+                    key_path,
+                    value
+                )
+            })?,
+        };
+        result.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,8 +762,8 @@ mod tests {
         assert_eq!(config.project.name, project_name);
         assert_eq!(config.check, CheckConfig::default());
         assert_eq!(config.tasks.len(), 2);
-        assert_eq!(config.tasks.get("build").unwrap(), &vec!["echo building...", "mkdir dist"]);
-        assert_eq!(config.tasks.get("deploy").unwrap(), &vec!["echo deploying..."]);
+        assert_eq!(config.tasks.get("build").unwrap().commands(), &["echo building...", "mkdir dist"]);
+        assert_eq!(config.tasks.get("deploy").unwrap().commands(), &["echo deploying..."]);
     }
 
     #[test]
@@ -124,10 +789,79 @@ mod tests {
         let config = load_config(tmp_dir.path()).unwrap();
 
         assert_eq!(config.project.name, project_name);
-        assert_eq!(config.check.linters, vec!["lint1"]);
+        assert_eq!(config.check.linters, vec![CheckStep::Command("lint1".to_string())]);
         assert!(config.check.testers.is_empty());
         assert_eq!(config.tasks.len(), 1);
-        assert_eq!(config.tasks.get("build").unwrap(), &vec!["build1"]);
+        assert_eq!(config.tasks.get("build").unwrap().commands(), &["build1"]);
+    }
+
+    #[test]
+    fn check_step_timeout_falls_back_to_the_check_level_default() {
+        let config_content = r#"
+[project]
+name = "check-step-timeout-project"
+
+[check]
+timeout = 30
+linters = ["ruff check .", { command = "mypy .", timeout = 120 }]
+"#;
+        let tmp_dir = tempdir().unwrap();
+        create_dummy_config(tmp_dir.path(), config_content);
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        let default_timeout = config.check.timeout_duration();
+        assert_eq!(config.check.linters[0].timeout_duration(default_timeout), Some(Duration::from_secs(30)));
+        assert_eq!(config.check.linters[1].timeout_duration(default_timeout), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn load_config_parses_the_build_backend_key() {
+        let config_content = r#"
+[project]
+name = "build-backend-project"
+
+[build]
+backend = "podman"
+"#;
+        let tmp_dir = tempdir().unwrap();
+        create_dummy_config(tmp_dir.path(), config_content);
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        assert_eq!(config.build.backend.as_deref(), Some("podman"));
+    }
+
+    #[test]
+    fn load_config_defaults_the_build_backend_to_none() {
+        let tmp_dir = tempdir().unwrap();
+        create_dummy_config(tmp_dir.path(), "[project]\nname = \"no-build-section\"\n");
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        assert_eq!(config.build.backend, None);
+    }
+
+    #[test]
+    fn load_config_parses_the_tag_with_git_key() {
+        let config_content = r#"
+[project]
+name = "build-tag-project"
+
+[build]
+tag_with_git = true
+"#;
+        let tmp_dir = tempdir().unwrap();
+        create_dummy_config(tmp_dir.path(), config_content);
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        assert!(config.build.tag_with_git);
+    }
+
+    #[test]
+    fn load_config_defaults_tag_with_git_to_false() {
+        let tmp_dir = tempdir().unwrap();
+        create_dummy_config(tmp_dir.path(), "[project]\nname = \"no-build-section\"\n");
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        assert!(!config.build.tag_with_git);
     }
 
     #[test]
@@ -155,6 +889,210 @@ mod tests {
         assert!(err.contains("invalid type") && err.contains("integer"));
     }
 
+    #[test]
+    fn load_config_parses_task_timeout_and_commands() {
+        let tmp_dir = tempdir().unwrap();
+        let project_name = "timeout-project";
+        let config_content = format!(
+            "[project]\nname = \"{}\"\n\n[check]\ntimeout = 30\n\n[tasks]\nquick = [\"echo fast\"]\nslow = {{ commands = [\"sleep 999\"], timeout = 5 }}",
+            project_name
+        );
+        create_dummy_config(tmp_dir.path(), &config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        assert_eq!(config.check.timeout_duration(), Some(Duration::from_secs(30)));
+        assert_eq!(config.tasks.get("quick").unwrap().timeout_duration(), None);
+        let slow = config.tasks.get("slow").unwrap();
+        assert_eq!(slow.commands(), &["sleep 999"]);
+        assert_eq!(slow.timeout_duration(), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn task_runner_defaults_to_shell() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = "[project]\nname = \"runner-project\"\n\n[tasks]\nbuild = [\"echo hi\"]";
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        assert_eq!(config.tasks.get("build").unwrap().runner(), "shell");
+    }
+
+    #[test]
+    fn task_runner_reads_docker_from_config() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content =
+            "[project]\nname = \"runner-project\"\n\n[tasks]\nbuild = { commands = [\"pytest\"], runner = \"docker\" }";
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        assert_eq!(config.tasks.get("build").unwrap().runner(), "docker");
+    }
+
+    #[test]
+    fn task_env_merges_global_and_task_overrides() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "env-project"
+
+[env]
+REGISTRY = "global-registry"
+LOG_LEVEL = "info"
+
+[tasks]
+build = { commands = ["echo hi"], env = { REGISTRY = "task-registry" } }
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        let task = config.tasks.get("build").unwrap();
+        let merged = config.task_env(task);
+
+        assert_eq!(merged.get("REGISTRY").map(String::as_str), Some("task-registry"));
+        assert_eq!(merged.get("LOG_LEVEL").map(String::as_str), Some("info"));
+    }
+
+    #[test]
+    fn global_env_is_empty_by_default() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = "[project]\nname = \"no-env-project\"";
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        assert!(config.global_env().is_empty());
+    }
+
+    #[test]
+    fn resolve_task_order_runs_dependencies_before_dependents() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "dag-project"
+
+[tasks]
+build = { commands = ["echo build"], dependencies = ["compile"] }
+compile = { commands = ["echo compile"], dependencies = ["fetch"] }
+fetch = ["echo fetch"]
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        let order = config.resolve_task_order("build").unwrap();
+
+        assert_eq!(order, vec!["fetch".to_string(), "compile".to_string(), "build".to_string()]);
+    }
+
+    #[test]
+    fn resolve_task_order_accepts_depends_on_as_an_alias_for_dependencies() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "depends-on-project"
+
+[tasks]
+build = { commands = ["echo build"], depends_on = ["compile"] }
+compile = ["echo compile"]
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        let order = config.resolve_task_order("build").unwrap();
+
+        assert_eq!(order, vec!["compile".to_string(), "build".to_string()]);
+    }
+
+    #[test]
+    fn resolve_task_order_accepts_needs_as_an_alias_for_dependencies() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "needs-project"
+
+[tasks]
+build = { commands = ["echo build"], needs = ["compile"] }
+compile = ["echo compile"]
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        let order = config.resolve_task_order("build").unwrap();
+
+        assert_eq!(order, vec!["compile".to_string(), "build".to_string()]);
+    }
+
+    #[test]
+    fn resolve_task_order_fails_on_cycle() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "cycle-project"
+
+[tasks]
+a = { commands = ["echo a"], dependencies = ["b"] }
+b = { commands = ["echo b"], dependencies = ["a"] }
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        let result = config.resolve_task_order("a");
+
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("Cycle detected") && err.contains('a') && err.contains('b'));
+    }
+
+    #[test]
+    fn resolve_task_order_fails_on_missing_dependency() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "missing-dep-project"
+
+[tasks]
+build = { commands = ["echo build"], dependencies = ["does_not_exist"] }
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        let result = config.resolve_task_order("build");
+
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("undefined task") && err.contains("does_not_exist"));
+    }
+
+    #[test]
+    fn task_condition_env_set_and_env_eq_are_evaluated_against_the_process_environment() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "condition-project"
+
+[tasks]
+needs_var = { commands = ["echo hi"], condition = { env_set = "AO_CONDITION_TEST_VAR" } }
+needs_value = { commands = ["echo hi"], condition = { env_eq = "AO_CONDITION_TEST_VAR", value = "expected" } }
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+        let config = load_config(tmp_dir.path()).unwrap();
+
+        std::env::remove_var("AO_CONDITION_TEST_VAR");
+        assert!(!config.tasks.get("needs_var").unwrap().condition().unwrap().is_met());
+        assert!(!config.tasks.get("needs_value").unwrap().condition().unwrap().is_met());
+
+        std::env::set_var("AO_CONDITION_TEST_VAR", "expected");
+        assert!(config.tasks.get("needs_var").unwrap().condition().unwrap().is_met());
+        assert!(config.tasks.get("needs_value").unwrap().condition().unwrap().is_met());
+        std::env::remove_var("AO_CONDITION_TEST_VAR");
+    }
+
+    #[test]
+    fn zero_timeout_means_no_limit() {
+        let mut check = CheckConfig::default();
+        check.timeout = Some(0);
+        assert_eq!(check.timeout_duration(), None);
+    }
+
     #[test]
     fn load_config_fails_if_file_missing() {
         let tmp_dir = tempdir().unwrap();
@@ -208,4 +1146,120 @@ mod tests {
         let err = result.unwrap_err().to_string();
         assert!(err.contains("invalid type") && err.contains("not-an-array"));
     }
+
+    #[test]
+    fn load_config_interpolates_env_vars_with_and_without_defaults() {
+        let tmp_dir = tempdir().unwrap();
+        std::env::set_var("AO_CONFIG_TEST_REGISTRY", "my-registry");
+        std::env::remove_var("AO_CONFIG_TEST_UNSET");
+        let config_content = r#"
+[project]
+name = "interp-project"
+
+[env]
+REGISTRY = "${AO_CONFIG_TEST_REGISTRY}"
+LOG_LEVEL = "${AO_CONFIG_TEST_UNSET:-info}"
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let config = load_config(tmp_dir.path()).unwrap();
+        assert_eq!(config.env.get("REGISTRY").map(String::as_str), Some("my-registry"));
+        assert_eq!(config.env.get("LOG_LEVEL").map(String::as_str), Some("info"));
+        std::env::remove_var("AO_CONFIG_TEST_REGISTRY");
+    }
+
+    #[test]
+    fn load_config_fails_on_undefined_interpolation_variable() {
+        let tmp_dir = tempdir().unwrap();
+        std::env::remove_var("AO_CONFIG_TEST_MISSING");
+        let config_content = r#"
+[project]
+name = "interp-fail-project"
+
+[env]
+TOKEN = "${AO_CONFIG_TEST_MISSING}"
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        let result = load_config(tmp_dir.path());
+        assert!(result.is_err());
+        let err = format!("{:#}", result.unwrap_err());
+        assert!(err.contains("AO_CONFIG_TEST_MISSING") && err.contains("env.TOKEN"));
+    }
+
+    #[test]
+    fn load_config_merges_global_user_config_with_project_wins_on_conflict() {
+        let project_dir = tempdir().unwrap();
+        let global_home = tempdir().unwrap();
+        let global_config_dir = global_home.path().join(".config").join("anops");
+        fs::create_dir_all(&global_config_dir).unwrap();
+        fs::write(
+            global_config_dir.join("config.toml"),
+            r#"
+[check]
+linters = ["global-lint"]
+timeout = 10
+
+[env]
+REGISTRY = "global-registry"
+"#,
+        )
+        .unwrap();
+
+        let config_content = r#"
+[project]
+name = "layered-project"
+
+[check]
+linters = ["project-lint"]
+timeout = 30
+
+[env]
+STAGE = "prod"
+"#;
+        create_dummy_config(project_dir.path(), config_content);
+
+        let previous_xdg = std::env::var("XDG_CONFIG_HOME").ok();
+        let previous_home = std::env::var("HOME").ok();
+        std::env::remove_var("XDG_CONFIG_HOME");
+        std::env::set_var("HOME", global_home.path());
+        let config = load_config(project_dir.path()).unwrap();
+        match previous_xdg {
+            Some(value) => std::env::set_var("XDG_CONFIG_HOME", value),
+            None => std::env::remove_var("XDG_CONFIG_HOME"),
+        }
+        match previous_home {
+            Some(value) => std::env::set_var("HOME", value),
+            None => std::env::remove_var("HOME"),
+        }
+
+        // Lists append (global entries first), scalars are overridden by the project layer,
+        // and keys only the global layer sets (REGISTRY) still come through.
+        assert_eq!(
+            config.check.linters,
+            vec![CheckStep::Command("global-lint".to_string()), CheckStep::Command("project-lint".to_string())]
+        );
+        assert_eq!(config.check.timeout_duration(), Some(Duration::from_secs(30)));
+        assert_eq!(config.env.get("REGISTRY").map(String::as_str), Some("global-registry"));
+        assert_eq!(config.env.get("STAGE").map(String::as_str), Some("prod"));
+    }
+
+    #[test]
+    fn load_config_applies_ao_double_underscore_env_overrides() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "env-override-project"
+
+[check]
+timeout = 10
+"#;
+        create_dummy_config(tmp_dir.path(), config_content);
+
+        std::env::set_var("AO__CHECK__TIMEOUT", "99");
+        let config = load_config(tmp_dir.path()).unwrap();
+        std::env::remove_var("AO__CHECK__TIMEOUT");
+
+        assert_eq!(config.check.timeout_duration(), Some(Duration::from_secs(99)));
+    }
 }
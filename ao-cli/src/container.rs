@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::utils::run_tool;
+
+/// Builds a container image from a directory containing a Dockerfile. `build::run` resolves one
+/// `ContainerBackend` per invocation (see `resolve_backend`) and calls it for both the
+/// api-service and model-service images, keeping the command-string construction separate from
+/// `run_tool` and leaving room for third parties to register their own backend.
+pub trait ContainerBackend {
+    /// Name of this backend, matching the `[build].backend` key in ao.toml (e.g. `"docker"`).
+    fn name(&self) -> &str;
+
+    /// Builds a single image from the Dockerfile in `context_dir`, applying every tag in `tags`
+    /// (e.g. `["myapp-api-service:latest", "myapp-api-service:abc1234"]` when
+    /// `[build].tag_with_git` is set) in one invocation.
+    fn build_image(&self, tags: &[String], context_dir: &Path) -> Result<()>;
+}
+
+/// Runs `{binary} build -t {tag1} -t {tag2} ... .` in `context_dir` via the shell, as `build::run`
+/// already did for `docker build`. Shared by every `ContainerBackend` impl in this module, since
+/// Docker, Podman, Buildah, and nerdctl all accept this same invocation shape.
+fn run_build_command(binary: &str, tags: &[String], context_dir: &Path) -> Result<()> {
+    let tag_flags: Vec<String> = tags.iter().map(|tag| format!("-t {}", tag)).collect();
+    let build_cmd = format!("{} build {} .", binary, tag_flags.join(" "));
+    run_tool(&build_cmd, context_dir, None, &BTreeMap::new())
+        .with_context(|| format!("Failed to build image(s) {:?} with '{}'", tags, binary))
+}
+
+/// Checks whether `binary` is runnable (i.e. its `--version` spawns successfully), used by
+/// `resolve_backend` to auto-detect an available backend when `[build].backend` isn't set.
+fn binary_is_available(binary: &str) -> bool {
+    std::process::Command::new(binary)
+        .arg("--version")
+        .output()
+        .is_ok()
+}
+
+pub struct DockerBackend;
+
+impl ContainerBackend for DockerBackend {
+    fn name(&self) -> &str {
+        "docker"
+    }
+
+    fn build_image(&self, tags: &[String], context_dir: &Path) -> Result<()> {
+        run_build_command("docker", tags, context_dir)
+    }
+}
+
+pub struct PodmanBackend;
+
+impl ContainerBackend for PodmanBackend {
+    fn name(&self) -> &str {
+        "podman"
+    }
+
+    fn build_image(&self, tags: &[String], context_dir: &Path) -> Result<()> {
+        run_build_command("podman", tags, context_dir)
+    }
+}
+
+pub struct BuildahBackend;
+
+impl ContainerBackend for BuildahBackend {
+    fn name(&self) -> &str {
+        "buildah"
+    }
+
+    fn build_image(&self, tags: &[String], context_dir: &Path) -> Result<()> {
+        run_build_command("buildah", tags, context_dir)
+    }
+}
+
+pub struct NerdctlBackend;
+
+impl ContainerBackend for NerdctlBackend {
+    fn name(&self) -> &str {
+        "nerdctl"
+    }
+
+    fn build_image(&self, tags: &[String], context_dir: &Path) -> Result<()> {
+        run_build_command("nerdctl", tags, context_dir)
+    }
+}
+
+/// Backend names tried, in order, when `[build].backend` is unset: the first one whose binary is
+/// runnable is used.
+const AUTO_DETECT_ORDER: &[&str] = &["docker", "podman", "buildah", "nerdctl"];
+
+/// Resolves `[build].backend` to a concrete `ContainerBackend`. `None` auto-detects the first
+/// available backend from `AUTO_DETECT_ORDER`.
+///
+/// # Errors
+///
+/// Returns an error if `name` doesn't match a known backend, or if auto-detection finds none of
+/// the known backends' binaries runnable.
+pub fn resolve_backend(name: Option<&str>) -> Result<Box<dyn ContainerBackend>> {
+    match name {
+        Some(name) => backend_for_name(name),
+        None => {
+            let detected = AUTO_DETECT_ORDER.iter().find(|candidate| binary_is_available(candidate)).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Could not auto-detect a container backend; none of {:?} were found on PATH. Set [build].backend explicitly.",
+                    AUTO_DETECT_ORDER
+                )
+            })?;
+            backend_for_name(detected)
+        }
+    }
+}
+
+fn backend_for_name(name: &str) -> Result<Box<dyn ContainerBackend>> {
+    match name {
+        "docker" => Ok(Box::new(DockerBackend)),
+        "podman" => Ok(Box::new(PodmanBackend)),
+        "buildah" => Ok(Box::new(BuildahBackend)),
+        "nerdctl" => Ok(Box::new(NerdctlBackend)),
+        other => bail!("Unknown container backend '{}'; expected one of {:?}", other, AUTO_DETECT_ORDER),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_backend_rejects_an_unknown_name() {
+        let result = resolve_backend(Some("rkt"));
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Unknown container backend"));
+    }
+
+    #[test]
+    fn resolve_backend_returns_the_matching_backend_for_each_known_name() {
+        for name in AUTO_DETECT_ORDER {
+            let backend = resolve_backend(Some(name)).unwrap();
+            assert_eq!(backend.name(), *name);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::config::{CoverageConfig, CoverageFormat};
+use crate::utils::expand_globs;
+
+/// Aggregated line/branch counts from one or more Cobertura-style coverage XML files.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CoverageTotals {
+    pub lines_covered: u64,
+    pub lines_valid: u64,
+    pub branches_covered: u64,
+    pub branches_valid: u64,
+}
+
+impl CoverageTotals {
+    fn add(&mut self, other: &CoverageTotals) {
+        self.lines_covered += other.lines_covered;
+        self.lines_valid += other.lines_valid;
+        self.branches_covered += other.branches_covered;
+        self.branches_valid += other.branches_valid;
+    }
+
+    /// Percentage of valid lines that are covered. `100.0` if there are no valid lines.
+    pub fn line_percent(&self) -> f64 {
+        if self.lines_valid == 0 {
+            100.0
+        } else {
+            self.lines_covered as f64 / self.lines_valid as f64 * 100.0
+        }
+    }
+
+    /// Percentage of valid branches that are covered. `100.0` if there are no valid branches.
+    pub fn branch_percent(&self) -> f64 {
+        if self.branches_valid == 0 {
+            100.0
+        } else {
+            self.branches_covered as f64 / self.branches_valid as f64 * 100.0
+        }
+    }
+}
+
+/// The result of merging every per-service coverage file matched by `[check.coverage].sources`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombinedCoverage {
+    pub totals: CoverageTotals,
+    /// Paths of the merged files, relative to the project root, sorted for deterministic output.
+    pub sources: Vec<String>,
+}
+
+/// Machine-consumable summary attached to the `--format json` check report.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageSummary {
+    pub line_percent: f64,
+    pub branch_percent: f64,
+    pub sources: Vec<String>,
+}
+
+impl From<&CombinedCoverage> for CoverageSummary {
+    fn from(combined: &CombinedCoverage) -> Self {
+        CoverageSummary {
+            line_percent: combined.totals.line_percent(),
+            branch_percent: combined.totals.branch_percent(),
+            sources: combined.sources.clone(),
+        }
+    }
+}
+
+/// Finds every file matched by `config.sources` under `project_root`, parses each as a
+/// Cobertura-style coverage XML document, and sums their line/branch counts. Returns `Ok(None)`
+/// if no tester emitted a coverage file, since aggregation is opt-in — not every project
+/// configures testers that produce one.
+///
+/// # Errors
+///
+/// Returns an error if a glob pattern is invalid, a matched file can't be read, or a matched file
+/// isn't a well-formed Cobertura `<coverage>` document.
+pub fn collect_coverage(project_root: &Path, config: &CoverageConfig) -> Result<Option<CombinedCoverage>> {
+    let matched = expand_globs(project_root, &config.sources)
+        .context("Failed to expand [check.coverage].sources glob patterns")?;
+    if matched.is_empty() {
+        return Ok(None);
+    }
+
+    let mut totals = CoverageTotals::default();
+    let mut sources = Vec::with_capacity(matched.len());
+    for path in &matched {
+        let file_totals = parse_cobertura_totals(path)
+            .with_context(|| format!("Failed to parse coverage file: {}", path.display()))?;
+        totals.add(&file_totals);
+        sources.push(relative_to(project_root, path));
+    }
+
+    Ok(Some(CombinedCoverage { totals, sources }))
+}
+
+fn relative_to(project_root: &Path, path: &Path) -> String {
+    path.strip_prefix(project_root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Extracts `lines-covered`/`lines-valid`/`branches-covered`/`branches-valid` from the root
+/// `<coverage ...>` tag of a Cobertura-style XML report (the format emitted by `pytest --cov`,
+/// `covr`, and most other coverage tools). Only the root tag's attributes are read; per-file
+/// breakdowns within the document are ignored.
+fn parse_cobertura_totals(path: &Path) -> Result<CoverageTotals> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read coverage file: {}", path.display()))?;
+    let tag_start = content
+        .find("<coverage")
+        .ok_or_else(|| anyhow!("No '<coverage>' root element found in '{}'", path.display()))?;
+    let tag_end = content[tag_start..]
+        .find('>')
+        .map(|offset| tag_start + offset)
+        .ok_or_else(|| anyhow!("Unterminated '<coverage' tag in '{}'", path.display()))?;
+    let tag = &content[tag_start..tag_end];
+
+    Ok(CoverageTotals {
+        lines_covered: extract_attr(tag, "lines-covered").unwrap_or(0),
+        lines_valid: extract_attr(tag, "lines-valid").unwrap_or(0),
+        branches_covered: extract_attr(tag, "branches-covered").unwrap_or(0),
+        branches_valid: extract_attr(tag, "branches-valid").unwrap_or(0),
+    })
+}
+
+/// Pulls `name="123"` out of an XML tag's attribute list, returning `None` if the attribute is
+/// absent or not a valid `u64`.
+fn extract_attr(tag: &str, name: &str) -> Option<u64> {
+    let needle = format!("{}=\"", name);
+    let start = tag.find(&needle)? + needle.len();
+    let end = tag[start..].find('"')? + start;
+    tag[start..end].parse().ok()
+}
+
+/// Writes the combined coverage report in the configured format. Logs a one-line summary
+/// regardless of format, so it's visible in `term` mode too.
+///
+/// # Errors
+///
+/// Returns an error if `format` is `xml`/`html` and `output` isn't set, or if writing the report
+/// file fails.
+pub fn write_report(combined: &CombinedCoverage, config: &CoverageConfig, project_root: &Path) -> Result<()> {
+    info!(
+        "Combined coverage: {:.1}% lines, {:.1}% branches (from {})",
+        combined.totals.line_percent(),
+        combined.totals.branch_percent(),
+        combined.sources.join(", "),
+    );
+
+    let (contents, what) = match config.format {
+        CoverageFormat::Term => return Ok(()),
+        CoverageFormat::Xml => (render_xml(combined), "XML"),
+        CoverageFormat::Html => (render_html(combined), "HTML"),
+    };
+
+    let output = config.output.as_ref().ok_or_else(|| {
+        anyhow!("[check.coverage].output must be set to write a combined {} report", what)
+    })?;
+    let output_path = project_root.join(output);
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(&output_path, contents)
+        .with_context(|| format!("Failed to write combined coverage {}: {}", what, output_path.display()))
+}
+
+fn render_xml(combined: &CombinedCoverage) -> String {
+    format!(
+        "<?xml version=\"1.0\" ?>\n<coverage line-rate=\"{:.4}\" branch-rate=\"{:.4}\" lines-covered=\"{}\" lines-valid=\"{}\" branches-covered=\"{}\" branches-valid=\"{}\" sources=\"{}\" />\n",
+        combined.totals.line_percent() / 100.0,
+        combined.totals.branch_percent() / 100.0,
+        combined.totals.lines_covered,
+        combined.totals.lines_valid,
+        combined.totals.branches_covered,
+        combined.totals.branches_valid,
+        combined.sources.join(","),
+    )
+}
+
+fn render_html(combined: &CombinedCoverage) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><head><title>Combined coverage</title></head><body>\n<h1>Combined coverage</h1>\n<p>Lines: {:.1}% ({}/{})</p>\n<p>Branches: {:.1}% ({}/{})</p>\n<p>Sources: {}</p>\n</body></html>\n",
+        combined.totals.line_percent(),
+        combined.totals.lines_covered,
+        combined.totals.lines_valid,
+        combined.totals.branch_percent(),
+        combined.totals.branches_covered,
+        combined.totals.branches_valid,
+        combined.sources.join(", "),
+    )
+}
+
+/// Fails if the combined line coverage is below `config.fail_under`. A missing threshold (the
+/// default) never fails.
+pub fn check_fail_under(combined: &CombinedCoverage, config: &CoverageConfig) -> Result<()> {
+    if let Some(threshold) = config.fail_under {
+        let actual = combined.totals.line_percent();
+        if actual < threshold {
+            bail!(
+                "Combined coverage {:.1}% is below the configured fail_under threshold of {:.1}%",
+                actual,
+                threshold
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CoverageConfig;
+    use tempfile::tempdir;
+
+    fn write_cobertura(path: &Path, lines_covered: u64, lines_valid: u64, branches_covered: u64, branches_valid: u64) {
+        let xml = format!(
+            "<?xml version=\"1.0\" ?>\n<coverage line-rate=\"0\" branch-rate=\"0\" lines-covered=\"{}\" lines-valid=\"{}\" branches-covered=\"{}\" branches-valid=\"{}\"><packages/></coverage>\n",
+            lines_covered, lines_valid, branches_covered, branches_valid,
+        );
+        fs::write(path, xml).unwrap();
+    }
+
+    #[test]
+    fn collect_coverage_returns_none_when_no_files_match() {
+        let tmp_dir = tempdir().unwrap();
+        let config = CoverageConfig::default();
+        let result = collect_coverage(tmp_dir.path(), &config).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn collect_coverage_sums_across_matched_files() {
+        let tmp_dir = tempdir().unwrap();
+        fs::create_dir_all(tmp_dir.path().join("api-service")).unwrap();
+        fs::create_dir_all(tmp_dir.path().join("model-service")).unwrap();
+        write_cobertura(&tmp_dir.path().join("api-service/coverage.xml"), 80, 100, 10, 20);
+        write_cobertura(&tmp_dir.path().join("model-service/coverage.xml"), 40, 100, 10, 20);
+
+        let config = CoverageConfig::default();
+        let combined = collect_coverage(tmp_dir.path(), &config).unwrap().unwrap();
+
+        assert_eq!(combined.totals.lines_covered, 120);
+        assert_eq!(combined.totals.lines_valid, 200);
+        assert_eq!(combined.sources.len(), 2);
+        assert!((combined.totals.line_percent() - 60.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn check_fail_under_fails_when_below_threshold() {
+        let combined = CombinedCoverage {
+            totals: CoverageTotals { lines_covered: 50, lines_valid: 100, branches_covered: 0, branches_valid: 0 },
+            sources: vec!["coverage.xml".to_string()],
+        };
+        let config = CoverageConfig { fail_under: Some(75.0), ..Default::default() };
+        let result = check_fail_under(&combined, &config);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("below the configured fail_under"));
+    }
+
+    #[test]
+    fn check_fail_under_passes_when_threshold_unset() {
+        let combined = CombinedCoverage {
+            totals: CoverageTotals { lines_covered: 1, lines_valid: 100, branches_covered: 0, branches_valid: 0 },
+            sources: vec!["coverage.xml".to_string()],
+        };
+        let config = CoverageConfig::default();
+        assert!(check_fail_under(&combined, &config).is_ok());
+    }
+
+    #[test]
+    fn write_report_requires_output_path_for_xml_format() {
+        let tmp_dir = tempdir().unwrap();
+        let combined = CombinedCoverage {
+            totals: CoverageTotals { lines_covered: 1, lines_valid: 1, branches_covered: 0, branches_valid: 0 },
+            sources: vec!["coverage.xml".to_string()],
+        };
+        let config = CoverageConfig { format: CoverageFormat::Xml, ..Default::default() };
+        let result = write_report(&combined, &config, tmp_dir.path());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("output"));
+    }
+
+    #[test]
+    fn write_report_writes_combined_xml_to_configured_path() {
+        let tmp_dir = tempdir().unwrap();
+        let combined = CombinedCoverage {
+            totals: CoverageTotals { lines_covered: 90, lines_valid: 100, branches_covered: 5, branches_valid: 10 },
+            sources: vec!["api-service/coverage.xml".to_string()],
+        };
+        let config = CoverageConfig {
+            format: CoverageFormat::Xml,
+            output: Some("coverage-combined.xml".to_string()),
+            ..Default::default()
+        };
+        write_report(&combined, &config, tmp_dir.path()).unwrap();
+        let contents = fs::read_to_string(tmp_dir.path().join("coverage-combined.xml")).unwrap();
+        assert!(contents.contains("lines-covered=\"90\""));
+    }
+}
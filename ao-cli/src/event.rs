@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+/// A single point-in-time occurrence during `ao check`, `ao build`, or `ao run`, emitted as one
+/// NDJSON line per event when `AO_OUTPUT=json` is set in the environment. This is independent of
+/// `--format json` (which, for `ao check` and `ao run`, captures output and prints one combined
+/// `Report` object at the end); the event stream is for tooling that wants to follow progress as it happens rather than
+/// parse a single end-of-run document.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event")]
+pub enum Event {
+    ProjectRootResolved { path: String },
+    ToolStarted { command: String },
+    ToolFinished { command: String, exit_code: i32, duration_ms: u128, success: bool },
+    BuildImageStarted { image: String },
+    BuildImageFinished { image: String, duration_ms: u128, success: bool },
+    TaskStarted { task: String },
+    TaskFinished { task: String, success: bool },
+    Summary { success: bool, duration_ms: u128 },
+}
+
+/// Whether the NDJSON event stream is enabled for this process, via `AO_OUTPUT=json`.
+pub fn enabled() -> bool {
+    std::env::var("AO_OUTPUT").map(|value| value == "json").unwrap_or(false)
+}
+
+/// Emits `event` as a single NDJSON line on stdout, if the event stream is enabled. A no-op
+/// otherwise, so call sites don't need to guard every call with `if event::enabled()`.
+pub fn emit(event: &Event) {
+    if !enabled() {
+        return;
+    }
+    match serde_json::to_string(event) {
+        Ok(line) => println!("{}", line),
+        Err(e) => tracing::warn!("Failed to serialize event {:?}: {}", event, e),
+    }
+}
+
+/// Convenience wrapper for `Event::ProjectRootResolved`.
+pub fn project_root_resolved(path: &Path) {
+    emit(&Event::ProjectRootResolved { path: path.display().to_string() });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_finished_serializes_with_an_event_tag() {
+        let event = Event::ToolFinished {
+            command: "echo hi".to_string(),
+            exit_code: 0,
+            duration_ms: 12,
+            success: true,
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"event\":\"ToolFinished\""));
+        assert!(json.contains("\"command\":\"echo hi\""));
+    }
+
+    #[test]
+    fn emit_is_a_no_op_when_ao_output_is_not_json() {
+        std::env::remove_var("AO_OUTPUT");
+        assert!(!enabled());
+        // emit() would print to stdout if enabled; nothing to assert on here beyond not panicking.
+        emit(&Event::Summary { success: true, duration_ms: 1 });
+    }
+}
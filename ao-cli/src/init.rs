@@ -1,7 +1,11 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tracing::info;
+use std::process::Command;
+use tracing::{info, warn};
+
+use crate::compose;
+use crate::config;
 
 // Basic placeholder content for generated files
 const DEFAULT_AO_TOML_CONTENT: &str = r#"[project]
@@ -53,41 +57,18 @@ docker-compose.override.yml
 /api-service/app/__pycache__/
 /model-service/app/__pycache__/
 /model-service/generated/ # Example for generated gRPC code
-"#;
-
-const DEFAULT_API_DOCKERFILE: &str = r#"# Use an official Python runtime as a parent image
-FROM python:3.11-slim
-
-WORKDIR /app
-
-# TODO: Add requirements.txt generation/copying
-# COPY requirements.txt .
-# RUN pip install --no-cache-dir -r requirements.txt
-
-COPY . .
-
-EXPOSE 8000
-
-# TODO: Replace with actual command e.g., uvicorn main:app
-CMD ["echo", "API Service Placeholder - Implement main:app and uncomment CMD"]
-"#;
-
-const DEFAULT_MODEL_DOCKERFILE: &str = r#"# Use an official Python runtime as a parent image
-FROM python:3.11-slim
-
-WORKDIR /app
-
-# TODO: Add requirements.txt generation/copying (including grpcio, grpcio-tools)
-# COPY requirements.txt .
-# RUN pip install --no-cache-dir -r requirements.txt
-
-# TODO: Copy generated gRPC code and model implementation
-COPY . .
-
-EXPOSE 50051
-
-# TODO: Replace with actual command e.g., python server.py
-CMD ["echo", "Model Service Placeholder - Implement server.py and uncomment CMD"]
+ao-cli.log
+.ao/
+/api-service/*_pb2.py
+/api-service/*_pb2.pyi
+/api-service/*_pb2_grpc.py
+/model-service/*_pb2.py
+/model-service/*_pb2.pyi
+/model-service/*_pb2_grpc.py
+
+# Build artifacts
+/target/
+*.o
 "#;
 
 const DEFAULT_DOCKER_COMPOSE: &str = r#"version: '3.8'
@@ -144,47 +125,60 @@ message PredictResponse {
 }
 "#;
 
-const DEFAULT_API_README: &str = r#"# AnOps API Service
-
-## Overview
-Acts as the RESTful entry point, receiving HTTP requests and communicating with the `model-service` via gRPC.
+const DEFAULT_INTERFACE_README: &str = r#"# AnOps Model Interface (gRPC)
 
-**Technology:** Python/FastAPI (default)
+Contains the Protocol Buffer (`.proto`) definitions for the gRPC interface between `api-service` and `model-service`.
 
-See root README and `ACTIONPLAN.md` for more details.
+See `anops.proto` and the root README for more details.
 "#;
 
-const DEFAULT_MODEL_README: &str = r#"# AnOps Model Service
+/// Scaffolds the files for one service (`api-service` or `model-service`) in a particular
+/// language. `run` writes the `(relative_path, contents)` pairs it returns under the service's
+/// directory without needing to know anything language-specific itself.
+pub trait ServiceBackend {
+    /// Files for `api-service`, relative to the `api-service/` directory.
+    fn api_service_files(&self) -> Vec<(&'static str, String)>;
+    /// Files for `model-service`, relative to the `model-service/` directory.
+    fn model_service_files(&self) -> Vec<(&'static str, String)>;
+}
+
+/// Scaffolds a Python/FastAPI `api-service` and a Python gRPC `model-service`.
+pub struct PythonBackend;
+
+impl ServiceBackend for PythonBackend {
+    fn api_service_files(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("README.md", r#"# AnOps API Service
 
 ## Overview
-Hosts the actual model code and implements the gRPC server defined in `model-interface`.
+Acts as the RESTful entry point, receiving HTTP requests and communicating with the `model-service` via gRPC.
 
-**Technology:** Python (default), R (planned)
+**Technology:** Python/FastAPI
 
 See root README and `ACTIONPLAN.md` for more details.
-"#;
+"#.to_string()),
+            ("Dockerfile", r#"# Use an official Python runtime as a parent image
+FROM python:3.11-slim
 
-const DEFAULT_INTERFACE_README: &str = r#"# AnOps Model Interface (gRPC)
+WORKDIR /app
 
-Contains the Protocol Buffer (`.proto`) definitions for the gRPC interface between `api-service` and `model-service`.
+# TODO: Add requirements.txt generation/copying
+# COPY requirements.txt .
+# RUN pip install --no-cache-dir -r requirements.txt
 
-See `anops.proto` and the root README for more details.
-"#;
+COPY . .
+
+EXPOSE 8000
 
-const API_SERVICE_REQUIREMENTS: &str = r#"fastapi>=0.100.0,<1.0.0
+# TODO: Replace with actual command e.g., uvicorn main:app
+CMD ["echo", "API Service Placeholder - Implement main:app and uncomment CMD"]
+"#.to_string()),
+            ("requirements.txt", r#"fastapi>=0.100.0,<1.0.0
 uvicorn[standard]>=0.20.0,<1.0.0
 grpcio>=1.50.0,<2.0.0
 python-json-logger>=2.0.0,<3.0.0
-"#;
-
-const MODEL_SERVICE_REQUIREMENTS: &str = r#"grpcio>=1.50.0,<2.0.0
-python-json-logger>=2.0.0,<3.0.0
-# Add other model dependencies here, e.g.:
-# pandas
-# scikit-learn
-"#;
-
-const API_SERVICE_MAIN_PY: &str = r#"# Placeholder main.py for api-service
+"#.to_string()),
+            ("main.py", r#"# Placeholder main.py for api-service
 from fastapi import FastAPI
 
 app = FastAPI()
@@ -194,9 +188,51 @@ def health_check():
     return {"status": "ok"}
 
 # TODO: Implement /predict endpoint
-"#;
+"#.to_string()),
+            ("tests/test_main.py", r#"# Placeholder test_main.py for api-service
+# TODO: Add tests using pytest and httpx
 
-const MODEL_SERVICE_SERVER_PY: &str = r#"# Placeholder server.py for model-service
+def test_placeholder():
+    assert True
+"#.to_string()),
+        ]
+    }
+
+    fn model_service_files(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("README.md", r#"# AnOps Model Service
+
+## Overview
+Hosts the actual model code and implements the gRPC server defined in `model-interface`.
+
+**Technology:** Python
+
+See root README and `ACTIONPLAN.md` for more details.
+"#.to_string()),
+            ("Dockerfile", r#"# Use an official Python runtime as a parent image
+FROM python:3.11-slim
+
+WORKDIR /app
+
+# TODO: Add requirements.txt generation/copying (including grpcio, grpcio-tools)
+# COPY requirements.txt .
+# RUN pip install --no-cache-dir -r requirements.txt
+
+# TODO: Copy generated gRPC code and model implementation
+COPY . .
+
+EXPOSE 50051
+
+# TODO: Replace with actual command e.g., python server.py
+CMD ["echo", "Model Service Placeholder - Implement server.py and uncomment CMD"]
+"#.to_string()),
+            ("requirements.txt", r#"grpcio>=1.50.0,<2.0.0
+python-json-logger>=2.0.0,<3.0.0
+# Add other model dependencies here, e.g.:
+# pandas
+# scikit-learn
+"#.to_string()),
+            ("server.py", r#"# Placeholder server.py for model-service
 import time
 from concurrent import futures
 import grpc
@@ -228,21 +264,355 @@ def serve():
 
 if __name__ == "__main__":
     serve()
-"#;
-
-const API_SERVICE_TEST_MAIN_PY: &str = r#"# Placeholder test_main.py for api-service
-# TODO: Add tests using pytest and httpx
+"#.to_string()),
+            ("tests/test_server.py", r#"# Placeholder test_server.py for model-service
+# TODO: Add tests using pytest and grpcio-testing
 
 def test_placeholder():
     assert True
-"#;
+"#.to_string()),
+        ]
+    }
+}
 
-const MODEL_SERVICE_TEST_SERVER_PY: &str = r#"# Placeholder test_server.py for model-service
-# TODO: Add tests using pytest and grpcio-testing
+/// Scaffolds an R `plumber` `api-service` and an R gRPC `model-service`, both managed with
+/// `renv` instead of `requirements.txt`.
+pub struct RBackend;
 
-def test_placeholder():
-    assert True
-"#;
+impl ServiceBackend for RBackend {
+    fn api_service_files(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("README.md", r#"# AnOps API Service
+
+## Overview
+Acts as the RESTful entry point, receiving HTTP requests and communicating with the `model-service` via gRPC.
+
+**Technology:** R/plumber
+
+See root README and `ACTIONPLAN.md` for more details.
+"#.to_string()),
+            ("Dockerfile", r#"FROM rocker/r-ver:4.3.2
+
+WORKDIR /app
+
+# TODO: Add DESCRIPTION/renv.lock restoration
+# COPY DESCRIPTION renv.lock .
+# RUN R -e "renv::restore()"
+
+COPY . .
+
+EXPOSE 8000
+
+CMD ["R", "-e", "pr <- plumber::plumb('plumber.R'); pr$run(host = '0.0.0.0', port = 8000)"]
+"#.to_string()),
+            ("DESCRIPTION", r#"Package: apiservice
+Type: Package
+Title: AnOps API Service
+Version: 0.1.0
+Imports:
+    plumber,
+    grpc
+"#.to_string()),
+            ("renv.lock", r#"{
+  "R": {
+    "Version": "4.3.2"
+  },
+  "Packages": {}
+}
+"#.to_string()),
+            ("plumber.R", r#"# Placeholder plumber.R for api-service
+library(plumber)
+
+#* @get /health
+function() {
+  list(status = "ok")
+}
+
+# TODO: Implement /predict endpoint, proxying to model-service over gRPC
+"#.to_string()),
+            ("tests/testthat/test-plumber.R", r#"# Placeholder test for api-service
+test_that("placeholder", {
+  expect_true(TRUE)
+})
+"#.to_string()),
+        ]
+    }
+
+    fn model_service_files(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("README.md", r#"# AnOps Model Service
+
+## Overview
+Hosts the actual model code and implements the gRPC server defined in `model-interface`.
+
+**Technology:** R
+
+See root README and `ACTIONPLAN.md` for more details.
+"#.to_string()),
+            ("Dockerfile", r#"FROM rocker/r-ver:4.3.2
+
+WORKDIR /app
+
+# TODO: Add DESCRIPTION/renv.lock restoration
+# COPY DESCRIPTION renv.lock .
+# RUN R -e "renv::restore()"
+
+# TODO: Copy generated gRPC code and model implementation
+COPY . .
+
+EXPOSE 50051
+
+CMD ["Rscript", "server.R"]
+"#.to_string()),
+            ("DESCRIPTION", r#"Package: modelservice
+Type: Package
+Title: AnOps Model Service
+Version: 0.1.0
+Imports:
+    grpc
+"#.to_string()),
+            ("renv.lock", r#"{
+  "R": {
+    "Version": "4.3.2"
+  },
+  "Packages": {}
+}
+"#.to_string()),
+            ("server.R", r#"# Placeholder server.R for model-service
+# TODO: Import generated gRPC code and implement the AnOps service
+
+serve <- function() {
+  message("Starting server. Listening on port 50051.")
+  # TODO: Construct and start the gRPC server
+}
+
+serve()
+"#.to_string()),
+            ("tests/testthat/test-server.R", r#"# Placeholder test for model-service
+test_that("placeholder", {
+  expect_true(TRUE)
+})
+"#.to_string()),
+        ]
+    }
+}
+
+/// Returns the scaffolding backend for a given language.
+pub fn backend_for(language: config::Language) -> Box<dyn ServiceBackend> {
+    match language {
+        config::Language::Python => Box::new(PythonBackend),
+        config::Language::R => Box::new(RBackend),
+    }
+}
+
+/// The `ao.toml` spelling of a `Language`, e.g. `language_name(Language::R) == "r"`.
+fn language_name(language: config::Language) -> &'static str {
+    match language {
+        config::Language::Python => "python",
+        config::Language::R => "r",
+    }
+}
+
+/// Enforces the Docker-image-name grammar on a derived project name, so it can't silently
+/// produce an unbuildable image tag downstream (see `build::run`'s `{project_name}-api-service`
+/// tags): must start with `[a-z0-9]`, followed by any number of `[a-z0-9_.-]`.
+fn validate_project_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let starts_ok = chars
+        .next()
+        .map(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        .unwrap_or(false);
+    let rest_ok = chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '.' | '-'));
+    if !starts_ok || !rest_ok {
+        bail!(
+            "Invalid project name '{}': must start with a lowercase letter or digit, and contain \
+             only lowercase letters, digits, '_', '.', or '-' (Docker image name grammar)",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Whether `path` exists and already contains at least one entry.
+fn directory_has_entries(path: &Path) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let mut entries = fs::read_dir(path)
+        .with_context(|| format!("Failed to read directory: {}", path.display()))?;
+    Ok(entries.next().is_some())
+}
+
+/// Glob-matches `pattern` (relative to `project_path`) and returns the matching paths.
+fn find_glob_matches(project_path: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let full_pattern = project_path.join(pattern);
+    let pattern_str = full_pattern.to_string_lossy().to_string();
+    let mut matches = Vec::new();
+    for entry in
+        glob::glob(&pattern_str).with_context(|| format!("Invalid glob pattern '{}'", pattern))?
+    {
+        matches.push(entry.with_context(|| format!("Failed to read glob match for pattern '{}'", pattern))?);
+    }
+    Ok(matches)
+}
+
+/// Infers the project's language from on-disk markers: a `requirements.txt`/`pyproject.toml`
+/// anywhere means Python; an R `DESCRIPTION` file with no Python markers means R. Defaults to
+/// Python if neither is found.
+fn detect_language(project_path: &Path) -> Result<config::Language> {
+    let has_python_marker = !find_glob_matches(project_path, "**/requirements.txt")?.is_empty()
+        || !find_glob_matches(project_path, "**/pyproject.toml")?.is_empty();
+    if has_python_marker {
+        return Ok(config::Language::Python);
+    }
+    let has_description = !find_glob_matches(project_path, "**/DESCRIPTION")?.is_empty();
+    if has_description {
+        return Ok(config::Language::R);
+    }
+    Ok(config::Language::Python)
+}
+
+/// Relative (forward-slash) paths of `tests`/`test` directories found anywhere in the project,
+/// used to seed `check.testers` with `pytest <dir>` entries.
+fn detect_test_dirs(project_path: &Path) -> Result<Vec<String>> {
+    let mut dirs = Vec::new();
+    for pattern in ["**/tests", "**/test"] {
+        for path in find_glob_matches(project_path, pattern)? {
+            if path.is_dir() {
+                if let Ok(relative) = path.strip_prefix(project_path) {
+                    dirs.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+    }
+    dirs.sort();
+    dirs.dedup();
+    Ok(dirs)
+}
+
+/// Relative (forward-slash) paths of every `.proto` file found anywhere in the project.
+fn detect_proto_files(project_path: &Path) -> Result<Vec<String>> {
+    let mut files: Vec<String> = find_glob_matches(project_path, "**/*.proto")?
+        .into_iter()
+        .filter_map(|path| path.strip_prefix(project_path).ok().map(|p| p.to_string_lossy().replace('\\', "/")))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Makes sure a detected `.proto` file ends up at the canonical `model-interface/anops.proto`
+/// path `generate_grpc_code` expects, without touching any other existing file. Does nothing if
+/// no `.proto` file was found, or one is already at that path.
+fn wire_proto_into_model_interface(project_path: &Path, proto_files: &[String]) -> Result<()> {
+    const CANONICAL: &str = "model-interface/anops.proto";
+    if proto_files.iter().any(|p| p == CANONICAL) {
+        return Ok(());
+    }
+    let Some(first) = proto_files.first() else {
+        return Ok(());
+    };
+
+    let interface_dir = project_path.join("model-interface");
+    fs::create_dir_all(&interface_dir)
+        .with_context(|| format!("Failed to create directory: {}", interface_dir.display()))?;
+    let dest = interface_dir.join("anops.proto");
+    fs::copy(project_path.join(first), &dest)
+        .with_context(|| format!("Failed to copy detected proto file '{}' into {}", first, CANONICAL))?;
+    info!("Wired detected proto file '{}' into {}", first, CANONICAL);
+
+    if proto_files.len() > 1 {
+        warn!(
+            "Multiple .proto files detected ({}); only '{}' was wired into {} (others left as-is)",
+            proto_files.join(", "),
+            first,
+            CANONICAL
+        );
+    }
+    Ok(())
+}
+
+/// Runs `git init` in `project_path` when `vcs` is `Git`, following cargo-temp's approach of
+/// offering VCS initialization as part of project scaffolding. Never fails the overall `ao init`
+/// run: a missing `git` binary or a non-zero exit just logs a warning and is skipped.
+fn maybe_init_vcs(project_path: &Path, vcs: config::VcsMode) {
+    if vcs != config::VcsMode::Git {
+        return;
+    }
+    match Command::new("git").arg("init").current_dir(project_path).output() {
+        Ok(output) if output.status.success() => {
+            info!("Initialized git repository in {}", project_path.display());
+        }
+        Ok(output) => {
+            warn!(
+                "'git init' exited with status {}; skipping VCS initialization ({})",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Err(e) => {
+            warn!("git binary not found on PATH ({}); skipping VCS initialization", e);
+        }
+    }
+}
+
+/// Generates `ao.toml` describing an already-existing, non-empty project directory. Its other
+/// files are left untouched (`.gitignore` is only written when missing); an existing `ao.toml`
+/// is replaced.
+fn detect_and_init(project_path: &Path, project_name: &str, vcs: config::VcsMode) -> Result<()> {
+    info!(
+        "Existing project contents detected at {}; generating ao.toml without overwriting files",
+        project_path.display()
+    );
+
+    let language = detect_language(project_path)?;
+    let test_dirs = detect_test_dirs(project_path)?;
+    let proto_files = detect_proto_files(project_path)?;
+    wire_proto_into_model_interface(project_path, &proto_files)?;
+
+    let testers_toml = if test_dirs.is_empty() {
+        "    # Example: Add commands to run tests\n    # \"pytest api-service/tests\",\n    # \"pytest model-service/tests\",\n".to_string()
+    } else {
+        test_dirs.iter().map(|dir| format!("    \"pytest {}\",\n", dir)).collect()
+    };
+
+    let config_path = project_path.join("ao.toml");
+    let config_content = format!(
+        r#"[project]
+name = "{}"
+language = "{}"
+
+[check]
+linters = []
+testers = [
+{}]
+
+[alias]
+b = "build"
+t = "test"
+
+[tasks]
+build = {{ commands = ["echo Building project..."], description = "Build the project" }}
+test = {{ commands = ["echo Running tests..."], description = "Run the test suite" }}
+"#,
+        project_name,
+        language_name(language),
+        testers_toml,
+    );
+    fs::write(&config_path, config_content)
+        .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+    info!("Created config file: {}", config_path.display());
+
+    let gitignore_path = project_path.join(".gitignore");
+    if !gitignore_path.exists() {
+        fs::write(&gitignore_path, DEFAULT_GITIGNORE_CONTENT)
+            .with_context(|| format!("Failed to write .gitignore: {}", gitignore_path.display()))?;
+        info!("Created .gitignore: {}", gitignore_path.display());
+    }
+
+    maybe_init_vcs(project_path, vcs);
+
+    Ok(())
+}
 
 /// Handler for `ao init`.
 /// Creates the basic project directory structure and configuration file.
@@ -250,11 +620,21 @@ def test_placeholder():
 /// # Arguments
 ///
 /// * `name` - The name of the project directory to initialize.
+/// * `language` - Default language backend used to scaffold `api-service`/`model-service`.
+/// * `api_service_language` - Overrides `language` for `api-service` only.
+/// * `model_service_language` - Overrides `language` for `model-service` only.
+/// * `vcs` - Whether to run `git init` in the new project directory.
 ///
 /// # Errors
 ///
 /// Returns an error if initialization fails (e.g., directory creation, file creation).
-pub fn run(path_str: String) -> Result<()> {
+pub fn run(
+    path_str: String,
+    language: config::Language,
+    api_service_language: Option<config::Language>,
+    model_service_language: Option<config::Language>,
+    vcs: config::VcsMode,
+) -> Result<()> {
     let project_path = PathBuf::from(path_str);
     info!("Initializing new project at: {}", project_path.display());
 
@@ -263,17 +643,34 @@ pub fn run(path_str: String) -> Result<()> {
         .file_name()
         .and_then(|name| name.to_str())
         .unwrap_or("anops-project"); // Default if path ends in .. or /
+    validate_project_name(project_name)?;
 
     // Create root project directory
     fs::create_dir_all(&project_path)
         .with_context(|| format!("Failed to create project directory: {}", project_path.display()))?;
 
+    // A non-empty target directory means there's already a project here; detect what's in it
+    // and describe it in ao.toml rather than overwriting files.
+    if directory_has_entries(&project_path)? {
+        return detect_and_init(&project_path, project_name, vcs);
+    }
+
     // Create ao.toml configuration file
     let config_path = project_path.join("ao.toml");
+    let language_overrides = format!(
+        "{}{}",
+        api_service_language
+            .map(|lang| format!("api_service_language = \"{}\"\n", language_name(lang)))
+            .unwrap_or_default(),
+        model_service_language
+            .map(|lang| format!("model_service_language = \"{}\"\n", language_name(lang)))
+            .unwrap_or_default(),
+    );
     let config_content = format!(
         r#"[project]
 name = "{}"
-
+language = "{}"
+{}
 [check]
 linters = []
 testers = [
@@ -282,52 +679,67 @@ testers = [
     # "pytest model-service/tests",
 ]
 
-# [tasks]
-# Define custom tasks here, e.g.:
-# build = ["echo Building project..."]
-# deploy = ["echo Deploying project..."]
+[alias]
+b = "build"
+t = "test"
+
+[tasks]
+build = {{ commands = ["echo Building project..."], description = "Build the project" }}
+test = {{ commands = ["echo Running tests..."], description = "Run the test suite" }}
 "#,
-        project_name // Use the extracted project name
+        project_name, // Use the extracted project name
+        language_name(language),
+        language_overrides,
     );
     fs::write(&config_path, config_content)
         .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
     info!("Created config file: {}", config_path.display());
 
-    // Create service directories
-    let services = ["api-service", "model-service", "model-interface"];
-    for service in services.iter() {
+    // Generate docker-compose.yml from the [services] table when one is declared; otherwise
+    // fall back to the default two-service topology.
+    let compose_path = project_path.join("docker-compose.yml");
+    let compose_content = {
+        let config = config::load_config(&project_path).context("Failed to load freshly-written config")?;
+        if config.services.is_empty() {
+            DEFAULT_DOCKER_COMPOSE.to_string()
+        } else {
+            compose::generate_compose_yaml(&config).context("Failed to generate docker-compose.yml from [services]")?
+        }
+    };
+    fs::write(&compose_path, compose_content)
+        .with_context(|| format!("Failed to write docker-compose.yml: {}", compose_path.display()))?;
+    info!("Created docker-compose.yml: {}", compose_path.display());
+
+    // Create service directories. api-service and model-service are scaffolded through the
+    // ServiceBackend trait, keeping this loop itself language-agnostic; model-interface is the
+    // same regardless of backend, since it only holds .proto definitions.
+    let api_backend = backend_for(api_service_language.unwrap_or(language));
+    let model_backend = backend_for(model_service_language.unwrap_or(language));
+    let services: [(&str, Option<Vec<(&'static str, String)>>); 3] = [
+        ("api-service", Some(api_backend.api_service_files())),
+        ("model-service", Some(model_backend.model_service_files())),
+        ("model-interface", None),
+    ];
+    for (service, files) in services {
         let service_path = project_path.join(service);
         fs::create_dir_all(&service_path)
             .with_context(|| format!("Failed to create directory: {}", service_path.display()))?;
         info!("Created directory: {}", service_path.display());
 
-        // Add placeholder files/READMEs using the correct constant names
-        match *service {
-            "api-service" => {
-                fs::write(service_path.join("README.md"), DEFAULT_API_README)?;
-                fs::write(service_path.join("Dockerfile"), DEFAULT_API_DOCKERFILE)?;
-                fs::write(service_path.join("requirements.txt"), API_SERVICE_REQUIREMENTS)?;
-                fs::write(service_path.join("main.py"), API_SERVICE_MAIN_PY)?;
-                // Create tests directory and placeholder test file
-                let test_dir = service_path.join("tests");
-                fs::create_dir_all(&test_dir)?;
-                fs::write(test_dir.join("test_main.py"), API_SERVICE_TEST_MAIN_PY)?;
-            }
-            "model-service" => {
-                fs::write(service_path.join("README.md"), DEFAULT_MODEL_README)?;
-                fs::write(service_path.join("Dockerfile"), DEFAULT_MODEL_DOCKERFILE)?;
-                fs::write(service_path.join("requirements.txt"), MODEL_SERVICE_REQUIREMENTS)?;
-                fs::write(service_path.join("server.py"), MODEL_SERVICE_SERVER_PY)?;
-                // Create tests directory and placeholder test file
-                let test_dir = service_path.join("tests");
-                fs::create_dir_all(&test_dir)?;
-                fs::write(test_dir.join("test_server.py"), MODEL_SERVICE_TEST_SERVER_PY)?;
+        match files {
+            Some(files) => {
+                for (relative_path, contents) in files {
+                    let file_path = service_path.join(relative_path);
+                    if let Some(parent) = file_path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&file_path, contents)?;
+                }
             }
-            "model-interface" => {
+            None => {
                 fs::write(service_path.join("README.md"), DEFAULT_INTERFACE_README)?;
                 fs::write(service_path.join("anops.proto"), DEFAULT_ANOP_PROTO)?;
             }
-            _ => {}
         }
     }
 
@@ -343,6 +755,8 @@ testers = [
         .with_context(|| format!("Failed to write .gitignore: {}", gitignore_path.display()))?;
     info!("Created .gitignore: {}", gitignore_path.display());
 
+    maybe_init_vcs(&project_path, vcs);
+
     info!("Project '{}' initialized successfully.", project_name);
     Ok(())
 }
@@ -359,7 +773,7 @@ mod tests {
         let project_name = "test_init_project";
         let project_path = tmp_dir.path().join(project_name);
         // Run the init command relative to the temp dir
-        let result = run(project_path.to_str().unwrap().to_string());
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None);
         assert!(result.is_ok(), "init::run failed: {:?}", result.err());
 
         // Check if base directory exists
@@ -377,6 +791,7 @@ mod tests {
         // Check if core files exist (as created by the current init::run)
         let core_files = [
             "ao.toml",
+            "docker-compose.yml",
             ".gitignore",
             "README.md", // Root README
             "api-service/Dockerfile",
@@ -409,6 +824,29 @@ mod tests {
         assert!(gitignore_content.contains("__pycache__/"));
         assert!(gitignore_content.contains("*.pyc"));
         // Clean up is handled by tempdir dropping
+
+        // Check the default docker-compose.yml was generated (no [services] declared yet).
+        let compose_content = fs::read_to_string(project_path.join("docker-compose.yml")).unwrap();
+        assert!(compose_content.contains("api-service"));
+        assert!(compose_content.contains("model-service"));
+    }
+
+    #[test]
+    fn run_initializes_a_git_repo_when_vcs_is_git() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("test_vcs_project");
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::Git);
+        assert!(result.is_ok(), "init::run failed: {:?}", result.err());
+        assert!(project_path.join(".git").is_dir());
+    }
+
+    #[test]
+    fn run_skips_vcs_initialization_when_vcs_is_none() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("test_no_vcs_project");
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None);
+        assert!(result.is_ok(), "init::run failed: {:?}", result.err());
+        assert!(!project_path.join(".git").exists());
     }
 
     #[test]
@@ -421,7 +859,7 @@ mod tests {
         if cfg!(unix) {
              let project_name = "/ao_init_fail_test";
              // Attempt to run the init command in a restricted path
-             let result = run(project_name.to_string());
+             let result = run(project_name.to_string(), config::Language::Python, None, None, config::VcsMode::None);
              // We expect this to fail, likely with a permission error context.
              assert!(result.is_err());
              assert!(result.unwrap_err().to_string().contains("Failed to create project directory"));
@@ -431,4 +869,115 @@ mod tests {
             println!("Skipping root directory creation test on non-Unix platform.");
         }
     }
+
+    #[test]
+    fn run_scaffolds_r_backend_when_requested() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("test_r_project");
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::R, None, None, config::VcsMode::None);
+        assert!(result.is_ok(), "init::run failed: {:?}", result.err());
+
+        assert!(project_path.join("api-service/plumber.R").is_file());
+        assert!(project_path.join("api-service/DESCRIPTION").is_file());
+        assert!(project_path.join("api-service/renv.lock").is_file());
+        assert!(project_path.join("model-service/server.R").is_file());
+        assert!(!project_path.join("api-service/requirements.txt").exists());
+
+        let config_content = fs::read_to_string(project_path.join("ao.toml")).unwrap();
+        assert!(config_content.contains("language = \"r\""));
+    }
+
+    #[test]
+    fn run_applies_per_service_language_override() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("test_mixed_project");
+        let result = run(
+            project_path.to_str().unwrap().to_string(),
+            config::Language::Python,
+            None,
+            Some(config::Language::R),
+            config::VcsMode::None,
+        );
+        assert!(result.is_ok(), "init::run failed: {:?}", result.err());
+
+        // api-service keeps the default (Python); model-service is overridden to R.
+        assert!(project_path.join("api-service/main.py").is_file());
+        assert!(project_path.join("model-service/server.R").is_file());
+        assert!(!project_path.join("model-service/server.py").exists());
+
+        let config_content = fs::read_to_string(project_path.join("ao.toml")).unwrap();
+        assert!(config_content.contains("model_service_language = \"r\""));
+    }
+
+    #[test]
+    fn run_rejects_project_names_that_are_not_valid_docker_image_names() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("Invalid_Name!");
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None);
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("Invalid project name"));
+        assert!(!project_path.exists(), "directory should not be created for an invalid name");
+    }
+
+    #[test]
+    fn run_accepts_a_project_name_that_matches_the_docker_grammar() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("my-valid_project.1");
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None);
+        assert!(result.is_ok(), "init::run failed: {:?}", result.err());
+    }
+
+    #[test]
+    fn run_detects_an_existing_python_project_instead_of_overwriting_it() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("existing_python_project");
+        fs::create_dir_all(project_path.join("api_service/tests")).unwrap();
+        fs::write(project_path.join("requirements.txt"), "fastapi\n").unwrap();
+        fs::write(project_path.join("main.py"), "# existing app\n").unwrap();
+
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None);
+        assert!(result.is_ok(), "init::run failed: {:?}", result.err());
+
+        // Pre-existing files are left untouched.
+        assert_eq!(fs::read_to_string(project_path.join("main.py")).unwrap(), "# existing app\n");
+        // No scaffolding directories are invented.
+        assert!(!project_path.join("model-service").exists());
+
+        let config_content = fs::read_to_string(project_path.join("ao.toml")).unwrap();
+        assert!(config_content.contains("language = \"python\""));
+        assert!(config_content.contains("pytest api_service/tests"));
+    }
+
+    #[test]
+    fn run_detects_an_existing_r_project() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("existing_r_project");
+        fs::create_dir_all(&project_path).unwrap();
+        fs::write(project_path.join("DESCRIPTION"), "Package: myrpkg\n").unwrap();
+        fs::write(project_path.join("plumber.R"), "# existing plumber app\n").unwrap();
+
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None);
+        assert!(result.is_ok(), "init::run failed: {:?}", result.err());
+
+        let config_content = fs::read_to_string(project_path.join("ao.toml")).unwrap();
+        assert!(config_content.contains("language = \"r\""));
+    }
+
+    #[test]
+    fn run_wires_a_detected_proto_file_into_model_interface() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = tmp_dir.path().join("existing_proto_project");
+        fs::create_dir_all(project_path.join("interface")).unwrap();
+        fs::write(project_path.join("interface/service.proto"), "syntax = \"proto3\";\n").unwrap();
+        fs::write(project_path.join("requirements.txt"), "fastapi\n").unwrap();
+
+        let result = run(project_path.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None);
+        assert!(result.is_ok(), "init::run failed: {:?}", result.err());
+
+        let wired = fs::read_to_string(project_path.join("model-interface/anops.proto")).unwrap();
+        assert_eq!(wired, "syntax = \"proto3\";\n");
+        // The original file is left in place.
+        assert!(project_path.join("interface/service.proto").is_file());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use tracing::info;
+
+use crate::config;
+use crate::utils::find_project_root;
+
+/// Handler for `ao list`.
+/// Prints every task defined in `[tasks]`, any `[alias]` entries that resolve to it, and its
+/// one-line `description`, in aligned columns (mirroring `cargo --list`'s built-in/alias table).
+///
+/// # Errors
+///
+/// Returns an error if the project root is not found or config loading fails.
+pub fn run(path_str: String) -> Result<()> {
+    let start_path = Path::new(&path_str);
+    info!("Listing tasks starting from '{}'", start_path.display());
+
+    let project_path = find_project_root(start_path)
+        .with_context(|| format!("Failed to find project root starting from '{}'", start_path.display()))?;
+    let config = config::load_config(&project_path)
+        .context("Failed to load project configuration")?;
+
+    let mut aliases_by_task: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (alias, target) in &config.alias {
+        aliases_by_task.entry(target.as_str()).or_default().push(alias.as_str());
+    }
+    for aliases in aliases_by_task.values_mut() {
+        aliases.sort();
+    }
+
+    let mut task_names: Vec<&String> = config.tasks.keys().collect();
+    task_names.sort();
+
+    if task_names.is_empty() {
+        println!("No tasks defined in ao.toml.");
+        return Ok(());
+    }
+
+    let rows: Vec<(String, String, &str)> = task_names
+        .iter()
+        .map(|name| {
+            let alias = aliases_by_task.get(name.as_str()).map(|a| a.join(", ")).unwrap_or_default();
+            let description = config.tasks[*name].description().unwrap_or("");
+            (name.to_string(), alias, description)
+        })
+        .collect();
+
+    let name_width = rows.iter().map(|(name, _, _)| name.len()).max().unwrap_or(0).max("TASK".len());
+    let alias_width = rows.iter().map(|(_, alias, _)| alias.len()).max().unwrap_or(0).max("ALIAS".len());
+
+    println!("{:name_width$}  {:alias_width$}  DESCRIPTION", "TASK", "ALIAS");
+    for (name, alias, description) in &rows {
+        println!("{:name_width$}  {:alias_width$}  {}", name, alias, description);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::init;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn setup_project_with_config(base_path: &Path, config_content: &str) -> std::path::PathBuf {
+        let project_dir = base_path.join("test_list_project");
+        init::run(project_dir.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None).unwrap();
+        fs::write(project_dir.join("ao.toml"), config_content).unwrap();
+        project_dir
+    }
+
+    #[test]
+    fn run_succeeds_with_no_tasks_defined() {
+        let tmp_dir = tempdir().unwrap();
+        let project_path = setup_project_with_config(
+            tmp_dir.path(),
+            "[project]\nname = \"test_list_project\"\n",
+        );
+        let result = run(project_path.to_str().unwrap().to_string());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn run_succeeds_with_tasks_aliases_and_descriptions() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"[project]
+name = "test_list_project"
+
+[alias]
+t = "test"
+b = "build"
+
+[tasks]
+build = { commands = ["echo building"], description = "Build the project" }
+test = { commands = ["echo testing"], description = "Run the test suite" }
+"#;
+        let project_path = setup_project_with_config(tmp_dir.path(), config_content);
+        let result = run(project_path.to_str().unwrap().to_string());
+        assert!(result.is_ok());
+    }
+}
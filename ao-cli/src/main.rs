@@ -1,14 +1,26 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use tracing_subscriber::{fmt, EnvFilter};
 use std::fs::OpenOptions;
 
-use ao::{init, check, run, build}; // Added build
+use ao::{init, check, run, build, list};
+use ao::config::{Language, VcsMode};
+use ao::utils::OutputFormat;
 
 /// Top-level CLI parser
 #[derive(Parser)]
 #[command(name = "ao", version = "0.1.0", about = "Analytics Ops CLI orchestrator")]
 struct Cli {
+    /// Start from this directory instead of the current one, like `cargo -C`. Replaces the path
+    /// argument of `check`, `run`, `build`, and `list` (`init` is unaffected, and the process's own
+    /// working directory is not changed), and is treated as the project root directly when it
+    /// already contains `ao.toml` (otherwise the usual upward search starts from there). Since
+    /// every subcommand resolves relative `[check]`/`[tasks]` paths against the discovered project
+    /// root rather than the process's real working directory, anchoring that search here makes
+    /// behavior identical whether `ao` is invoked from the project root or a subdirectory.
+    #[arg(short = 'C', long = "root", alias = "directory", global = true)]
+    root: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -20,12 +32,31 @@ enum Commands {
     Init {
         /// Name of the project to initialize
         name: String,
+        /// Language backend used to scaffold api-service and model-service
+        #[arg(long, value_enum, default_value = "python")]
+        language: Language,
+        /// Overrides `--language` for api-service only
+        #[arg(long, value_enum)]
+        api_service_language: Option<Language>,
+        /// Overrides `--language` for model-service only
+        #[arg(long, value_enum)]
+        model_service_language: Option<Language>,
+        /// Initialize a VCS repo in the new project directory, or skip entirely
+        #[arg(long, value_enum, default_value = "git")]
+        vcs: VcsMode,
     },
     /// Run linting and tests on a project
     Check {
         /// Path to the project directory
         #[arg(default_value = ".")]
         path: String,
+        /// Output format: human-readable text, or a single JSON report for CI tooling
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+        /// Only lint/test files changed relative to this git ref (substituted into any linter
+        /// or tester command containing a `{changed_files}` placeholder)
+        #[arg(long)]
+        changed_since: Option<String>,
     },
     /// Run a defined task from ao.toml
     Run {
@@ -34,12 +65,34 @@ enum Commands {
         /// Path within the project directory (optional, defaults to current dir)
         #[arg(default_value = ".")]
         path: String,
+        /// Output format: human-readable text, or a single JSON report for CI tooling
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+        /// Ignore cached input/output fingerprints and always run the task
+        #[arg(long)]
+        force: bool,
+        /// Max number of independent dependency tasks to run concurrently (default: available
+        /// parallelism)
+        #[arg(long, short = 'j')]
+        jobs: Option<usize>,
+        /// Extra arguments appended to the task's command(s), e.g. `ao run test -- -k foo`
+        #[arg(trailing_var_arg = true, num_args = 0.., allow_hyphen_values = true)]
+        args: Vec<String>,
     },
     /// Build Docker images for the project services
     Build {
         /// Path within the project directory (optional, defaults to current dir)
         #[arg(default_value = ".")]
         path: String,
+        /// Ignore cached content hashes and rebuild every image, even if unchanged
+        #[arg(long, alias = "no-cache")]
+        force: bool,
+    },
+    /// List the tasks defined in ao.toml, along with their aliases and descriptions
+    List {
+        /// Path within the project directory (optional, defaults to current dir)
+        #[arg(default_value = ".")]
+        path: String,
     },
 }
 
@@ -58,11 +111,26 @@ fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
+    // `-C`/`--root` takes priority over a subcommand's own path argument, much like cargo's `-C`;
+    // the process's working directory itself stays where it was.
+    let root = cli
+        .root
+        .map(|root| {
+            std::fs::canonicalize(&root)
+                .with_context(|| format!("Failed to canonicalize --root path: {}", root))
+                .map(|p| p.to_string_lossy().to_string())
+        })
+        .transpose()?;
+    let resolve_path = |path: String| root.clone().unwrap_or(path);
+
     match cli.command {
-        Commands::Init { name } => init::run(name)?,
-        Commands::Check { path } => check::run(path)?,
-        Commands::Run { task_name, path } => run::run(task_name, path)?,
-        Commands::Build { path } => build::run(path)?,
+        Commands::Init { name, language, api_service_language, model_service_language, vcs } =>
+            init::run(name, language, api_service_language, model_service_language, vcs)?,
+        Commands::Check { path, format, changed_since } => check::run(resolve_path(path), format, changed_since)?,
+        Commands::Run { task_name, path, format, force, jobs, args } =>
+            run::run(task_name, resolve_path(path), format, force, args, jobs)?,
+        Commands::Build { path, force } => build::run(resolve_path(path), force)?,
+        Commands::List { path } => list::run(resolve_path(path))?,
     }
 
     Ok(())
@@ -0,0 +1,288 @@
+use crate::config::Config;
+use crate::utils::build_runner;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+use tracing::info;
+
+struct QueueState {
+    in_degree: HashMap<String, usize>,
+    dependents: HashMap<String, Vec<String>>,
+    ready: VecDeque<String>,
+    in_flight: usize,
+    remaining: usize,
+    error: Option<anyhow::Error>,
+}
+
+/// A dependency graph over an already topologically-valid set of tasks (see
+/// `Config::resolve_task_order`), scheduled so independent branches run concurrently: each
+/// node's in-degree counts its unfinished dependencies within the set, nodes join the ready
+/// queue once their last dependency completes, and worker threads in
+/// `run_dependencies_in_parallel` drain it until every node has run or the first failure stops
+/// further scheduling.
+struct DependencyQueue {
+    state: Mutex<QueueState>,
+    condvar: Condvar,
+}
+
+impl DependencyQueue {
+    /// Builds a queue for exactly `task_names`, wiring an edge from `b` to `a` whenever `a`
+    /// lists `b` in its `dependencies()` and `b` is also in `task_names`. Dependencies outside
+    /// the set are ignored. The target task itself is not in the set: the caller runs it
+    /// separately once this queue drains.
+    fn for_tasks(config: &Config, task_names: &[String]) -> Self {
+        let set: std::collections::HashSet<&str> = task_names.iter().map(String::as_str).collect();
+        let mut in_degree = HashMap::new();
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+        for name in task_names {
+            let task = &config.tasks[name];
+            let needed = task.dependencies().iter().filter(|dep| set.contains(dep.as_str())).count();
+            in_degree.insert(name.clone(), needed);
+            for dep in task.dependencies() {
+                if set.contains(dep.as_str()) {
+                    dependents.entry(dep.clone()).or_default().push(name.clone());
+                }
+            }
+        }
+
+        let ready: VecDeque<String> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        DependencyQueue {
+            state: Mutex::new(QueueState {
+                in_degree,
+                dependents,
+                ready,
+                in_flight: 0,
+                remaining: task_names.len(),
+                error: None,
+            }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Blocks until a task is ready to run, returning `None` once the queue has fully drained or
+    /// another worker has already recorded a failure (new scheduling stops, but callers that are
+    /// mid-task are expected to finish and report via `complete`).
+    fn next(&self) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.error.is_some() || state.remaining == 0 {
+                return None;
+            }
+            if let Some(name) = state.ready.pop_front() {
+                state.in_flight += 1;
+                return Some(name);
+            }
+            state = self.condvar.wait(state).unwrap();
+        }
+    }
+
+    /// Records that `name` finished. On success, decrements the in-degree of each dependent and
+    /// moves any that reach zero onto the ready queue. On failure, records the first error seen
+    /// (later ones are dropped) so `next` stops handing out further work.
+    fn complete(&self, name: &str, result: Result<()>) {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight -= 1;
+        state.remaining -= 1;
+        match result {
+            Ok(()) => {
+                if let Some(dependents) = state.dependents.remove(name) {
+                    for dependent in dependents {
+                        if let Some(degree) = state.in_degree.get_mut(&dependent) {
+                            *degree -= 1;
+                            if *degree == 0 {
+                                state.ready.push_back(dependent);
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                if state.error.is_none() {
+                    state.error = Some(e);
+                }
+            }
+        }
+        self.condvar.notify_all();
+    }
+
+    fn into_error(self) -> Option<anyhow::Error> {
+        self.state.into_inner().unwrap().error
+    }
+}
+
+/// Runs `dependency_names` (a topologically-valid set, e.g. `resolve_task_order(target)` minus
+/// `target` itself) across up to `jobs` worker threads, so independent branches of the
+/// dependency graph execute concurrently instead of strictly sequentially. A task only starts
+/// once every dependency it shares with the set has finished successfully.
+///
+/// # Errors
+///
+/// Returns the first error encountered by any task. Once a task fails, no further tasks are
+/// scheduled, though tasks already running are allowed to finish.
+pub fn run_dependencies_in_parallel(
+    config: &Config,
+    project_path: &Path,
+    dependency_names: &[String],
+    jobs: usize,
+) -> Result<()> {
+    if dependency_names.is_empty() {
+        return Ok(());
+    }
+
+    let queue = DependencyQueue::for_tasks(config, dependency_names);
+    let jobs = jobs.max(1);
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                while let Some(task_name) = queue.next() {
+                    info!("--- Running dependency task '{}' (parallel) ---", task_name);
+                    let result = run_one_task(config, project_path, &task_name);
+                    queue.complete(&task_name, result);
+                }
+            });
+        }
+    });
+
+    match queue.into_error() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Runs a single dependency task's commands: applies its merged env, skips it entirely if its
+/// `condition` isn't met, then runs its commands against its configured `ToolRunner` backend.
+/// Like the sequential dependency execution it replaces, a dependency task doesn't participate in
+/// incremental fingerprinting or `--format json` reporting.
+fn run_one_task(config: &Config, project_path: &Path, task_name: &str) -> Result<()> {
+    let task = config
+        .tasks
+        .get(task_name)
+        .with_context(|| format!("Dependency task '{}' not found in ao.toml", task_name))?;
+
+    if let Some(condition) = task.condition() {
+        if !condition.is_met() {
+            info!("Skipping dependency task '{}': condition not met", task_name);
+            return Ok(());
+        }
+    }
+
+    let env = config.task_env(task);
+    let timeout = task.timeout_duration();
+    let runner = build_runner(task.runner(), project_path, timeout)
+        .with_context(|| format!("Failed to set up runner for dependency task '{}'", task_name))?;
+    for command_str in task.commands() {
+        runner.run(command_str, project_path, &env).with_context(|| {
+            format!("Command '{}' in dependency task '{}' failed", command_str, task_name)
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::init;
+    use std::fs;
+    use std::time::Instant;
+    use tempfile::tempdir;
+
+    fn setup_project_with_config(base_path: &Path, config_content: &str) -> std::path::PathBuf {
+        let project_dir = base_path.join("test_scheduler_project");
+        init::run(project_dir.to_str().unwrap().to_string(), config::Language::Python, None, None, config::VcsMode::None).unwrap();
+        fs::write(project_dir.join("ao.toml"), config_content).unwrap();
+        project_dir
+    }
+
+    #[test]
+    fn run_dependencies_in_parallel_runs_independent_branches_concurrently() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "test_scheduler_project"
+
+[tasks]
+build = { commands = ["echo build"], dependencies = ["left", "right"] }
+left = ["sleep 0.3"]
+right = ["sleep 0.3"]
+"#;
+        let project_path = setup_project_with_config(tmp_dir.path(), config_content);
+        let config = config::load_config(&project_path).unwrap();
+        let order = config.resolve_task_order("build").unwrap();
+        let dependency_names = order[..order.len() - 1].to_vec();
+
+        let start = Instant::now();
+        let result = run_dependencies_in_parallel(&config, &project_path, &dependency_names, 2);
+        let elapsed = start.elapsed();
+
+        assert!(result.is_ok(), "run_dependencies_in_parallel failed: {:?}", result.err());
+        assert!(
+            elapsed.as_secs_f64() < 0.55,
+            "expected 'left' and 'right' to run concurrently (~0.3s), took {:?}",
+            elapsed
+        );
+    }
+
+    #[test]
+    fn run_dependencies_in_parallel_stops_scheduling_after_first_failure() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "test_scheduler_project"
+
+[tasks]
+build = { commands = ["echo build"], dependencies = ["broken"] }
+broken = ["ls non_existent_file_in_scheduler_test"]
+"#;
+        let project_path = setup_project_with_config(tmp_dir.path(), config_content);
+        let config = config::load_config(&project_path).unwrap();
+        let order = config.resolve_task_order("build").unwrap();
+        let dependency_names = order[..order.len() - 1].to_vec();
+
+        let result = run_dependencies_in_parallel(&config, &project_path, &dependency_names, 2);
+
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("broken"));
+    }
+
+    #[test]
+    fn run_dependencies_in_parallel_respects_a_diamond_shaped_graph() {
+        let tmp_dir = tempdir().unwrap();
+        let config_content = r#"
+[project]
+name = "test_scheduler_project"
+
+[tasks]
+build = { commands = ["sh -c 'echo build >> order.txt'"], dependencies = ["left", "right"] }
+left = { commands = ["sh -c 'echo left >> order.txt'"], dependencies = ["fetch"] }
+right = { commands = ["sh -c 'echo right >> order.txt'"], dependencies = ["fetch"] }
+fetch = ["sh -c 'echo fetch >> order.txt'"]
+"#;
+        let project_path = setup_project_with_config(tmp_dir.path(), config_content);
+        let config = config::load_config(&project_path).unwrap();
+        let order = config.resolve_task_order("build").unwrap();
+        let dependency_names = order[..order.len() - 1].to_vec();
+
+        let result = run_dependencies_in_parallel(&config, &project_path, &dependency_names, 4);
+        assert!(result.is_ok(), "run_dependencies_in_parallel failed: {:?}", result.err());
+
+        let lines: Vec<String> =
+            fs::read_to_string(project_path.join("order.txt")).unwrap().lines().map(str::to_string).collect();
+        // "fetch" must precede both "left" and "right"; "build" itself never ran here (the
+        // caller runs the target task separately once this queue drains).
+        let fetch_idx = lines.iter().position(|l| l == "fetch").unwrap();
+        let left_idx = lines.iter().position(|l| l == "left").unwrap();
+        let right_idx = lines.iter().position(|l| l == "right").unwrap();
+        assert!(fetch_idx < left_idx);
+        assert!(fetch_idx < right_idx);
+        assert!(!lines.contains(&"build".to_string()));
+    }
+}